@@ -1,3 +1,4 @@
 pub mod client;
 pub mod models;
 pub mod http_client;
+pub mod source_code;
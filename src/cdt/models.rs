@@ -6,28 +6,78 @@ pub type RuntimeScriptId = String;
 pub type DebuggerCallFrameId = String;
 pub type RuntimeRemoteObjectId = String;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Exactly one of the fields may be set, a call argument without any field is `undefined`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct RuntimeCallArgument {
-    value: Value,
-    unserializable_value: String,
-    object_id: RuntimeRemoteObjectId
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unserializable_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_id: Option<RuntimeRemoteObjectId>,
+}
+
+impl RuntimeCallArgument {
+    pub fn from_remote_object(
+        remote_object: &RuntimeRemoteObjectResult,
+    ) -> Result<RuntimeCallArgument, Error> {
+        if let Some(object_id) = &remote_object.object_id {
+            return Ok(RuntimeCallArgument {
+                object_id: Some(object_id.to_owned()),
+                ..RuntimeCallArgument::default()
+            });
+        }
+
+        if let Some(unserializable_value) = &remote_object.unserializable_value {
+            return Ok(RuntimeCallArgument {
+                unserializable_value: Some(unserializable_value.to_owned()),
+                ..RuntimeCallArgument::default()
+            });
+        }
+
+        let value = match (remote_object.r#type.as_str(), &remote_object.value) {
+            ("undefined", _) => None,
+            ("object", None) => Some(Value::Null),
+            (_, value) => Some(serde_json::to_value(value)?),
+        };
+
+        Ok(RuntimeCallArgument {
+            value,
+            ..RuntimeCallArgument::default()
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct RuntimeRemoteObject {
     pub result: RuntimeRemoteObjectResult,
+    pub exception_details: Option<RuntimeExceptionDetails>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeExceptionDetails {
+    pub text: String,
+    #[serde(default)]
+    pub line_number: u32,
+    #[serde(default)]
+    pub column_number: u32,
+    pub exception: Option<RuntimeRemoteObjectResult>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct RuntimeRemoteObjectResult {
     pub r#type: String,
+    pub subtype: Option<String>,
     pub object_id: Option<String>,
     pub value: Option<RuntimeRemoteObjectResultValue>,
+    pub unserializable_value: Option<String>,
     pub description: Option<String>,
     pub class_name: Option<String>,
+    pub preview: Option<RuntimeObjectPreview>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -35,7 +85,37 @@ pub struct RuntimeRemoteObjectResult {
 pub enum RuntimeRemoteObjectResultValue {
     Bool(bool),
     String(String),
-    Number(i32),
+    Number(f64),
+    Json(Value),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeObjectPreview {
+    pub r#type: String,
+    pub subtype: Option<String>,
+    pub description: Option<String>,
+    pub overflow: bool,
+    pub properties: Vec<RuntimePropertyPreview>,
+    #[serde(default)]
+    pub entries: Vec<RuntimeEntryPreview>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimePropertyPreview {
+    pub name: String,
+    pub r#type: String,
+    pub subtype: Option<String>,
+    pub value: Option<String>,
+    pub value_preview: Option<RuntimeObjectPreview>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeEntryPreview {
+    pub key: Option<RuntimeObjectPreview>,
+    pub value: RuntimeObjectPreview,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -49,23 +129,99 @@ pub struct ResultResponse {
 #[serde(rename_all = "camelCase")]
 pub struct RuntimeExecutionContextDestroyed {
     method: String,
-    params: RuntimeExecutionContextDestroyedParams,
+    pub params: RuntimeExecutionContextDestroyedParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeExecutionContextCreated {
+    pub params: RuntimeExecutionContextCreatedParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeExecutionContextCreatedParams {
+    pub context: RuntimeExecutionContextDescription,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeExecutionContextDescription {
+    pub id: RuntimeExecutionContextId,
+    pub origin: String,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapProfilerAddHeapSnapshotChunk {
+    pub params: HeapProfilerAddHeapSnapshotChunkParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapProfilerAddHeapSnapshotChunkParams {
+    pub chunk: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapProfilerReportHeapSnapshotProgress {
+    pub params: HeapProfilerReportHeapSnapshotProgressParams,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapProfilerReportHeapSnapshotProgressParams {
+    pub done: u64,
+    pub total: u64,
+    #[serde(default)]
+    pub finished: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct RuntimeExecutionContextDestroyedParams {
-    execution_context_id: RuntimeExecutionContextId,
+    pub execution_context_id: RuntimeExecutionContextId,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorResponse {
+    pub id: i32,
+    pub error: ErrorResponseError,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorResponseError {
+    pub code: i32,
+    pub message: String,
+    pub data: Option<String>,
+}
+
+impl std::fmt::Display for ErrorResponseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.data {
+            Some(data) => write!(f, "{} ({})", self.message, data),
+            None => write!(f, "{}", self.message),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Response {
     DebuggerScriptParsed(ScriptParsedResponse),
     DebuggerPaused(DebuggerPausedResponse),
+    DebuggerResumed,
     ResultScriptSource(ResultScriptSourceResponse),
-    ResultRuntimeRemoteObject(RuntimeRemoteObject),
+    ResultRuntimeRemoteObject(Box<RuntimeRemoteObject>),
+    RuntimeExecutionContextCreated(RuntimeExecutionContextCreated),
     RuntimeExecutionContextDestroyed(RuntimeExecutionContextDestroyed),
+    HeapProfilerAddHeapSnapshotChunk(HeapProfilerAddHeapSnapshotChunk),
+    HeapProfilerReportHeapSnapshotProgress(HeapProfilerReportHeapSnapshotProgress),
     Result(ResultResponse),
+    Error(ErrorResponse),
     Unknown(Value),
 }
 
@@ -77,14 +233,6 @@ impl Response {
             None
         }
     }
-
-    pub fn expect_runtime_remote_object(&self) -> Option<&RuntimeRemoteObject> {
-        if let Response::ResultRuntimeRemoteObject(res) = self {
-            Some(res)
-        } else {
-            None
-        }
-    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -105,29 +253,18 @@ pub struct DebuggerScriptParsedResponseParams {
     pub start_column: i32,
     pub start_line: i32,
     pub url: String,
-    #[serde(rename = "sourceMapURL")]
+    #[serde(rename = "sourceMapURL", default)]
     pub source_map_url: String,
-    #[serde(rename = "hasSourceURL")]
+    #[serde(rename = "hasSourceURL", default)]
     pub has_source_url: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-pub struct RuntimeRunScriptRequestParams {
-    pub script_id: RuntimeScriptId,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "camelCase")]
-pub struct DebuggerGetPossibleBreakpointsParams {
-    pub start: DebuggerLocation,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct DebuggerLocation {
     pub script_id: RuntimeScriptId,
     pub line_number: u32,
+    #[serde(default)]
     pub column_number: u32,
 }
 
@@ -141,6 +278,46 @@ pub struct DebuggerPausedResponse {
 #[serde(rename_all = "camelCase")]
 pub struct DebuggerPausedParams {
     pub call_frames: Vec<DebuggerPausedCallFrame>,
+    #[serde(default)]
+    pub reason: String,
+    pub data: Option<Value>,
+    #[serde(default)]
+    pub hit_breakpoints: Vec<String>,
+    pub async_stack_trace: Option<RuntimeStackTrace>,
+    pub async_stack_trace_id: Option<RuntimeStackTraceId>,
+    pub async_call_stack_trace_id: Option<RuntimeStackTraceId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeStackTrace {
+    pub description: Option<String>,
+    pub call_frames: Vec<RuntimeCallFrame>,
+    pub parent: Option<Box<RuntimeStackTrace>>,
+    pub parent_id: Option<RuntimeStackTraceId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeStackTraceId {
+    pub id: String,
+    pub debugger_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeCallFrame {
+    pub function_name: String,
+    pub script_id: RuntimeScriptId,
+    pub url: String,
+    pub line_number: i32,
+    pub column_number: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DebuggerGetStackTraceResult {
+    pub stack_trace: RuntimeStackTrace,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -150,6 +327,72 @@ pub struct DebuggerPausedCallFrame {
     pub function_name: String,
     pub function_location: DebuggerLocation,
     pub location: DebuggerLocation,
+    #[serde(default)]
+    pub scope_chain: Vec<DebuggerScope>,
+    pub return_value: Option<RuntimeRemoteObjectResult>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DebuggerScope {
+    pub r#type: String,
+    pub object: RuntimeRemoteObjectResult,
+    pub name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DebuggerSetBreakpointResult {
+    pub breakpoint_id: String,
+    pub locations: Vec<DebuggerLocation>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DebuggerSetBreakpointAtLocationResult {
+    pub breakpoint_id: String,
+    pub actual_location: DebuggerLocation,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DebuggerSetBreakpointOnFunctionCallResult {
+    pub breakpoint_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DebuggerSetScriptSourceResult {
+    pub call_frames: Option<Vec<DebuggerPausedCallFrame>>,
+    pub exception_details: Option<RuntimeExceptionDetails>,
+    pub status: Option<String>,
+}
+
+impl DebuggerSetScriptSourceResult {
+    pub fn is_ok(&self) -> bool {
+        self.exception_details.is_none() && self.status.as_deref().unwrap_or("Ok") == "Ok"
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimePropertyDescriptor {
+    pub name: String,
+    pub value: Option<RuntimeRemoteObjectResult>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeGetPropertiesResult {
+    pub result: Vec<RuntimePropertyDescriptor>,
+    #[serde(default)]
+    pub internal_properties: Vec<RuntimePropertyDescriptor>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeGlobalLexicalScopeNamesResult {
+    pub names: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -164,6 +407,107 @@ pub struct ResultScriptSourceResponseResult {
     pub script_source: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilerProfile {
+    pub nodes: Vec<ProfilerProfileNode>,
+    pub start_time: f64,
+    pub end_time: f64,
+    #[serde(default)]
+    pub samples: Vec<u32>,
+    #[serde(default)]
+    pub time_deltas: Vec<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilerProfileNode {
+    pub id: u32,
+    pub call_frame: RuntimeCallFrame,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deopt_reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position_ticks: Option<Vec<ProfilerPositionTickInfo>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilerPositionTickInfo {
+    pub line: u32,
+    pub ticks: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilerStopResult {
+    pub profile: ProfilerProfile,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilerTakePreciseCoverageResult {
+    pub result: Vec<ProfilerScriptCoverage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilerScriptCoverage {
+    pub script_id: RuntimeScriptId,
+    pub url: String,
+    pub functions: Vec<ProfilerFunctionCoverage>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilerFunctionCoverage {
+    pub function_name: String,
+    pub ranges: Vec<ProfilerCoverageRange>,
+    pub is_block_coverage: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilerCoverageRange {
+    pub start_offset: usize,
+    pub end_offset: usize,
+    pub count: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapProfilerStopSamplingResult {
+    pub profile: HeapProfilerSamplingHeapProfile,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapProfilerSamplingHeapProfile {
+    pub head: HeapProfilerSamplingHeapProfileNode,
+    #[serde(default)]
+    pub samples: Vec<HeapProfilerSamplingHeapProfileSample>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapProfilerSamplingHeapProfileNode {
+    pub call_frame: RuntimeCallFrame,
+    pub self_size: f64,
+    pub id: u32,
+    pub children: Vec<HeapProfilerSamplingHeapProfileNode>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapProfilerSamplingHeapProfileSample {
+    pub size: f64,
+    pub node_id: u32,
+    pub ordinal: f64,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Request {
@@ -6,8 +6,8 @@ pub struct SourceCode {
     pub source_mapping: Option<SourceMap>,
 }
 
-static MAPPING_START: &'static str = "//# sourceMappingURL=";
-static BASE64_CODING: &'static str = "data:application/json;charset=utf-8;base64";
+static MAPPING_START: &str = "//# sourceMappingURL=";
+static BASE64_CODING: &str = "data:application/json;charset=utf-8;base64";
 
 impl SourceCode {
     pub fn from_str(input: &str) -> SourceCode {
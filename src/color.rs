@@ -0,0 +1,31 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub static GRAY: &str = "\x1b[90m";
+pub static GREEN: &str = "\x1b[32m";
+pub static YELLOW: &str = "\x1b[33m";
+pub static MAGENTA: &str = "\x1b[35m";
+pub static CYAN: &str = "\x1b[36m";
+pub static RED: &str = "\x1b[31m";
+pub static LIGHT_RED: &str = "\x1b[91m";
+pub static LIGHT_YELLOW: &str = "\x1b[93m";
+pub static BOLD: &str = "\x1b[1m";
+static RESET: &str = "\x1b[0m";
+
+static COLORS_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turn colors off when stdout is redirected, so the output of batch sessions and pipes
+/// doesn't contain escape codes.
+pub fn disable_colors_unless_terminal() {
+    if !std::io::stdout().is_terminal() {
+        COLORS_ENABLED.store(false, Ordering::Relaxed);
+    }
+}
+
+pub fn colored(color: &str, text: &str) -> String {
+    if COLORS_ENABLED.load(Ordering::Relaxed) {
+        format!("{}{}{}", color, text, RESET)
+    } else {
+        text.to_owned()
+    }
+}
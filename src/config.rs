@@ -0,0 +1,32 @@
+use serde::Deserialize;
+
+pub static DEFAULT_CONFIG_FILE_PATH: &str = ".node-debug.json";
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Config {
+    /// REPL commands executed every time the debugger stops.
+    pub display: Vec<String>,
+    /// Regular expressions of script URLs the debugger steps over and hides in backtraces.
+    pub blackbox: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            display: Vec::new(),
+            blackbox: vec![
+                "^node:".to_owned(),
+                "^internal/".to_owned(),
+                "/node_modules/".to_owned(),
+            ],
+        }
+    }
+}
+
+impl Config {
+    pub fn from_file(path: &str) -> Result<Config, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use crate::color::{colored, GRAY};
+
+use super::dominator_tree::DominatorTree;
+use super::snapshot::{HeapSnapshot, HeapSnapshotResult};
+
+static TOP_CONSTRUCTORS: usize = 30;
+static ROOT: usize = 0;
+
+#[derive(Default)]
+pub struct ConstructorSummary {
+    pub count: u64,
+    pub shallow_size: u64,
+    pub retained_size: u64,
+}
+
+pub fn analyze_command(path: &str) -> HeapSnapshotResult<()> {
+    println!("Reading {}...", path);
+    let snapshot = HeapSnapshot::from_file(path)?;
+
+    println!("Computing dominators of {} nodes...", snapshot.node_count());
+    let dominator_tree = DominatorTree::new(&snapshot);
+    let summary = summarize_constructors(&snapshot, &dominator_tree);
+
+    let total_size = dominator_tree.retained_size(ROOT);
+    let mut constructors = summary.into_iter().collect::<Vec<_>>();
+    constructors.sort_by_key(|(_, summary)| std::cmp::Reverse(summary.retained_size));
+
+    println!("Total reachable size: {}", format_size(total_size));
+    let header = format!(
+        "{:>10} {:>12} {:>12} {:>6}  constructor",
+        "count", "shallow", "retained", "%"
+    );
+    println!("{}", colored(GRAY, &header));
+
+    for (constructor, summary) in constructors.into_iter().take(TOP_CONSTRUCTORS) {
+        println!(
+            "{:>10} {:>12} {:>12} {:>5.1}%  {}",
+            summary.count,
+            format_size(summary.shallow_size),
+            format_size(summary.retained_size),
+            summary.retained_size as f64 / total_size.max(1) as f64 * 100.0,
+            constructor
+        );
+    }
+
+    Ok(())
+}
+
+/// Group the reachable nodes by constructor. The retained size of a constructor only counts
+/// objects which aren't dominated by another object of the same constructor, otherwise
+/// nested objects (e.g. linked list items) would be counted many times.
+pub fn summarize_constructors(
+    snapshot: &HeapSnapshot,
+    dominator_tree: &DominatorTree,
+) -> HashMap<String, ConstructorSummary> {
+    let mut constructor_ids: HashMap<String, usize> = HashMap::new();
+    let node_constructors = (0..snapshot.node_count())
+        .map(|node| {
+            let constructor = snapshot.constructor_name(node);
+            let next_id = constructor_ids.len();
+            *constructor_ids.entry(constructor).or_insert(next_id)
+        })
+        .collect::<Vec<usize>>();
+
+    let mut summaries = (0..constructor_ids.len())
+        .map(|_| ConstructorSummary::default())
+        .collect::<Vec<_>>();
+
+    // Synthetic nodes (the root, GC roots) aren't objects, they'd only retain everything.
+    let is_counted = (0..snapshot.node_count())
+        .map(|node| dominator_tree.is_reachable(node) && snapshot.node_type(node) != "synthetic")
+        .collect::<Vec<bool>>();
+
+    for node in 0..snapshot.node_count() {
+        if is_counted[node] {
+            let summary = &mut summaries[node_constructors[node]];
+            summary.count += 1;
+            summary.shallow_size += snapshot.self_size(node);
+        }
+    }
+
+    let mut children = vec![Vec::new(); snapshot.node_count()];
+    for node in 0..snapshot.node_count() {
+        if let Some(dominator) = dominator_tree.dominator(node) {
+            children[dominator].push(node);
+        }
+    }
+
+    // Walk the dominator tree keeping the number of nodes of each constructor on the path
+    // from the root, a node is counted if it's the topmost one of its constructor.
+    let mut on_path = vec![0; constructor_ids.len()];
+    let mut stack = vec![(ROOT, false)];
+
+    while let Some((node, is_exit)) = stack.pop() {
+        let constructor = node_constructors[node];
+
+        if is_exit {
+            on_path[constructor] -= 1;
+            continue;
+        }
+
+        if is_counted[node] && on_path[constructor] == 0 {
+            summaries[constructor].retained_size += dominator_tree.retained_size(node);
+        }
+
+        on_path[constructor] += 1;
+        stack.push((node, true));
+        stack.extend(children[node].iter().map(|child| (*child, false)));
+    }
+
+    constructor_ids
+        .into_iter()
+        .map(|(constructor, id)| (constructor, std::mem::take(&mut summaries[id])))
+        .filter(|(_, summary)| summary.count > 0)
+        .collect()
+}
+
+pub fn format_size(bytes: u64) -> String {
+    let units = ["B", "kB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1000.0 && unit < units.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, units[unit])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
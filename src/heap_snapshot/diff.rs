@@ -0,0 +1,232 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::color::{colored, GRAY};
+
+use super::analyze::format_size;
+use super::snapshot::{HeapSnapshot, HeapSnapshotResult, NodeLocation};
+
+static TOP_GROWING: usize = 20;
+
+#[derive(Default, Debug, Clone, Copy)]
+pub struct ObjectCount {
+    pub count: u64,
+    pub size: u64,
+}
+
+/// Object counts of a snapshot, small enough to be kept around after the snapshot is dropped.
+#[derive(Debug)]
+pub struct HeapSnapshotSummary {
+    pub constructors: HashMap<String, ObjectCount>,
+    pub allocation_sites: HashMap<NodeLocation, ObjectCount>,
+    pub script_urls: HashMap<u64, String>,
+}
+
+impl HeapSnapshotSummary {
+    pub fn new(snapshot: &HeapSnapshot) -> HeapSnapshotSummary {
+        let mut constructors: HashMap<String, ObjectCount> = HashMap::new();
+        let mut allocation_sites: HashMap<NodeLocation, ObjectCount> = HashMap::new();
+
+        for node in 0..snapshot.node_count() {
+            if snapshot.node_type(node) == "synthetic" {
+                continue;
+            }
+
+            let object_count = constructors
+                .entry(snapshot.constructor_name(node))
+                .or_default();
+            object_count.count += 1;
+            object_count.size += snapshot.self_size(node);
+        }
+
+        for (node, location) in snapshot.locations() {
+            let object_count = allocation_sites.entry(location.clone()).or_default();
+            object_count.count += 1;
+            object_count.size += snapshot.self_size(*node);
+        }
+
+        HeapSnapshotSummary {
+            constructors,
+            allocation_sites,
+            script_urls: snapshot.script_urls(),
+        }
+    }
+
+    pub fn from_file(path: &str) -> HeapSnapshotResult<HeapSnapshotSummary> {
+        Ok(HeapSnapshotSummary::new(&HeapSnapshot::from_file(path)?))
+    }
+}
+
+pub fn diff_command(before_path: &str, after_path: &str) -> HeapSnapshotResult<()> {
+    println!("Reading {}...", before_path);
+    let before = HeapSnapshotSummary::from_file(before_path)?;
+    println!("Reading {}...", after_path);
+    let after = HeapSnapshotSummary::from_file(after_path)?;
+
+    // Script ids are only unique within a process, prefer the URL found in the last
+    // snapshot as that's where the growing allocation sites are.
+    print_growth(&[&before, &after], |location| {
+        let script = after
+            .script_urls
+            .get(&location.script_id)
+            .or_else(|| before.script_urls.get(&location.script_id))
+            .cloned()
+            .unwrap_or_else(|| format!("script {}", location.script_id));
+
+        format!(
+            "{}:{}:{}",
+            script,
+            location.line_number + 1,
+            location.column_number + 1
+        )
+    });
+
+    Ok(())
+}
+
+/// Print constructors and allocation sites whose instance count never decreased across the
+/// snapshots and grew overall, sorted by the number of new instances.
+pub fn print_growth<F>(summaries: &[&HeapSnapshotSummary], mut format_location: F)
+where
+    F: FnMut(&NodeLocation) -> String,
+{
+    let constructors = summaries
+        .iter()
+        .map(|summary| &summary.constructors)
+        .collect::<Vec<_>>();
+    let allocation_sites = summaries
+        .iter()
+        .map(|summary| &summary.allocation_sites)
+        .collect::<Vec<_>>();
+
+    let growing_constructors = find_growing(&constructors);
+    let growing_allocation_sites = find_growing(&allocation_sites);
+
+    if growing_constructors.is_empty() {
+        println!("No constructor grew.");
+    } else {
+        print_growth_header("constructor");
+
+        for (constructor, first, last) in growing_constructors.into_iter().take(TOP_GROWING) {
+            print_growth_row(first, last, constructor);
+        }
+    }
+
+    if !growing_allocation_sites.is_empty() {
+        print_growth_header("allocation site");
+
+        for (location, first, last) in growing_allocation_sites.into_iter().take(TOP_GROWING) {
+            print_growth_row(first, last, &format_location(location));
+        }
+    }
+}
+
+fn find_growing<'a, K: Eq + Hash>(
+    counts: &[&'a HashMap<K, ObjectCount>],
+) -> Vec<(&'a K, ObjectCount, ObjectCount)> {
+    let keys = counts
+        .iter()
+        .flat_map(|counts| counts.keys())
+        .collect::<HashSet<_>>();
+
+    let mut growing = keys
+        .into_iter()
+        .filter_map(|key| {
+            let key_counts = counts
+                .iter()
+                .map(|counts| counts.get(key).copied().unwrap_or_default())
+                .collect::<Vec<_>>();
+            let first = *key_counts.first()?;
+            let last = *key_counts.last()?;
+            let never_decreased = key_counts
+                .windows(2)
+                .all(|window| window[1].count >= window[0].count);
+
+            if never_decreased && last.count > first.count {
+                Some((key, first, last))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    growing.sort_by_key(|(_, first, last)| std::cmp::Reverse(last.count - first.count));
+    growing
+}
+
+fn print_growth_header(name: &str) {
+    let header = format!(
+        "{:>10} {:>10} {:>12}  {}",
+        "new", "count", "size delta", name
+    );
+    println!("{}", colored(GRAY, &header));
+}
+
+fn print_growth_row(first: ObjectCount, last: ObjectCount, name: &str) {
+    let size_delta = if last.size >= first.size {
+        format!("+{}", format_size(last.size - first.size))
+    } else {
+        format!("-{}", format_size(first.size - last.size))
+    };
+
+    println!(
+        "{:>10} {:>10} {:>12}  {}",
+        format!("+{}", last.count - first.count),
+        last.count,
+        size_delta,
+        name
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{find_growing, HeapSnapshotSummary, ObjectCount};
+    use crate::heap_snapshot::snapshot::HeapSnapshot;
+
+    #[test]
+    fn summary() {
+        let json = include_str!("fixtures/graph.heapsnapshot");
+        let summary = HeapSnapshotSummary::new(&HeapSnapshot::from_json(json).unwrap());
+
+        let a = summary.constructors["A"];
+        assert_eq!((a.count, a.size), (2, 20));
+        assert!(!summary.constructors.contains_key(""));
+        assert_eq!(summary.allocation_sites.len(), 2);
+        assert_eq!(summary.script_urls[&3], "/app.js");
+    }
+
+    #[test]
+    fn growing_counts() {
+        let counts = |pairs: &[(&'static str, u64)]| {
+            pairs
+                .iter()
+                .map(|(key, count)| {
+                    (
+                        *key,
+                        ObjectCount {
+                            count: *count,
+                            size: 0,
+                        },
+                    )
+                })
+                .collect::<HashMap<_, _>>()
+        };
+        let first = counts(&[("steady", 1), ("growing", 1), ("fluctuating", 1)]);
+        let second = counts(&[("steady", 1), ("growing", 2), ("fluctuating", 0)]);
+        let third = counts(&[
+            ("steady", 1),
+            ("growing", 5),
+            ("fluctuating", 9),
+            ("new", 2),
+        ]);
+
+        let growing = find_growing(&[&first, &second, &third])
+            .into_iter()
+            .map(|(key, _, last)| (*key, last.count))
+            .collect::<Vec<_>>();
+
+        assert_eq!(growing, [("growing", 5), ("new", 2)]);
+    }
+}
@@ -0,0 +1,230 @@
+use super::snapshot::HeapSnapshot;
+
+static ROOT: usize = 0;
+static UNREACHABLE: usize = usize::MAX;
+
+/// Immediate dominators and retained sizes of the snapshot nodes, computed with the
+/// iterative algorithm from Cooper, Harvey and Kennedy, "A Simple, Fast Dominance
+/// Algorithm". Nodes unreachable from the root through strong references are ignored.
+pub struct DominatorTree {
+    dominators: Vec<usize>,
+    retained_sizes: Vec<u64>,
+}
+
+impl DominatorTree {
+    pub fn new(snapshot: &HeapSnapshot) -> DominatorTree {
+        let node_count = snapshot.node_count();
+        let post_order = post_order(snapshot);
+
+        let mut post_order_index = vec![UNREACHABLE; node_count];
+        for (i, node) in post_order.iter().enumerate() {
+            post_order_index[*node] = i;
+        }
+
+        let predecessors = Predecessors::new(snapshot, &post_order_index);
+
+        // Dominators are indexed by post-order index while they're computed.
+        let root_index = post_order.len() - 1;
+        let mut dominators = vec![UNREACHABLE; post_order.len()];
+        dominators[root_index] = root_index;
+
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            for index in (0..root_index).rev() {
+                let mut new_dominator = UNREACHABLE;
+
+                for predecessor in predecessors.get(index) {
+                    if dominators[*predecessor] == UNREACHABLE {
+                        continue;
+                    }
+
+                    new_dominator = if new_dominator == UNREACHABLE {
+                        *predecessor
+                    } else {
+                        intersect(&dominators, *predecessor, new_dominator)
+                    };
+                }
+
+                if new_dominator != UNREACHABLE && dominators[index] != new_dominator {
+                    dominators[index] = new_dominator;
+                    changed = true;
+                }
+            }
+        }
+
+        let mut node_dominators = vec![UNREACHABLE; node_count];
+        for (index, dominator) in dominators.iter().enumerate() {
+            node_dominators[post_order[index]] = post_order[*dominator];
+        }
+
+        // A node is always visited after everything it dominates in the post-order, so its
+        // retained size is complete when it's added to its dominator.
+        let mut retained_sizes = (0..node_count)
+            .map(|node| snapshot.self_size(node))
+            .collect::<Vec<u64>>();
+
+        for node in &post_order {
+            if *node != ROOT {
+                retained_sizes[node_dominators[*node]] += retained_sizes[*node];
+            }
+        }
+
+        DominatorTree {
+            dominators: node_dominators,
+            retained_sizes,
+        }
+    }
+
+    pub fn is_reachable(&self, node: usize) -> bool {
+        self.dominators[node] != UNREACHABLE
+    }
+
+    pub fn retained_size(&self, node: usize) -> u64 {
+        self.retained_sizes[node]
+    }
+
+    /// Immediate dominator of the node, `None` for the root and unreachable nodes.
+    pub fn dominator(&self, node: usize) -> Option<usize> {
+        let dominator = self.dominators[node];
+
+        if node == ROOT || dominator == UNREACHABLE {
+            None
+        } else {
+            Some(dominator)
+        }
+    }
+}
+
+fn intersect(dominators: &[usize], mut a: usize, mut b: usize) -> usize {
+    while a != b {
+        while a < b {
+            a = dominators[a];
+        }
+
+        while b < a {
+            b = dominators[b];
+        }
+    }
+
+    a
+}
+
+/// Iterative DFS from the root, recursion would overflow the stack on long object chains.
+fn post_order(snapshot: &HeapSnapshot) -> Vec<usize> {
+    let mut visited = vec![false; snapshot.node_count()];
+    let mut post_order = Vec::new();
+    let mut stack = vec![(ROOT, snapshot.strong_references(ROOT).collect::<Vec<_>>())];
+    visited[ROOT] = true;
+
+    while let Some((node, references)) = stack.last_mut() {
+        match references.pop() {
+            Some(reference) if !visited[reference] => {
+                visited[reference] = true;
+                let references = snapshot.strong_references(reference).collect();
+                stack.push((reference, references));
+            }
+            Some(_) => {}
+            None => {
+                post_order.push(*node);
+                stack.pop();
+            }
+        }
+    }
+
+    post_order
+}
+
+/// Predecessors of the reachable nodes by post-order index, stored in a single vector.
+struct Predecessors {
+    offsets: Vec<usize>,
+    predecessors: Vec<usize>,
+}
+
+impl Predecessors {
+    fn new(snapshot: &HeapSnapshot, post_order_index: &[usize]) -> Predecessors {
+        let reachable_count = post_order_index
+            .iter()
+            .filter(|index| **index != UNREACHABLE)
+            .count();
+        let mut counts = vec![0; reachable_count + 1];
+        let reachable_edges = || {
+            (0..snapshot.node_count())
+                .filter(|node| post_order_index[*node] != UNREACHABLE)
+                .flat_map(|node| {
+                    snapshot
+                        .strong_references(node)
+                        .map(move |reference| (post_order_index[node], post_order_index[reference]))
+                })
+        };
+
+        for (_, to) in reachable_edges() {
+            counts[to + 1] += 1;
+        }
+
+        for i in 1..counts.len() {
+            counts[i] += counts[i - 1];
+        }
+
+        let offsets = counts.clone();
+        let mut predecessors = vec![0; offsets[reachable_count]];
+
+        for (from, to) in reachable_edges() {
+            predecessors[counts[to]] = from;
+            counts[to] += 1;
+        }
+
+        Predecessors {
+            offsets,
+            predecessors,
+        }
+    }
+
+    fn get(&self, index: usize) -> &[usize] {
+        &self.predecessors[self.offsets[index]..self.offsets[index + 1]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DominatorTree;
+    use crate::heap_snapshot::snapshot::HeapSnapshot;
+
+    fn dominator_tree() -> DominatorTree {
+        let json = include_str!("fixtures/graph.heapsnapshot");
+        DominatorTree::new(&HeapSnapshot::from_json(json).unwrap())
+    }
+
+    #[test]
+    fn dominators() {
+        let dominator_tree = dominator_tree();
+
+        assert_eq!(dominator_tree.dominator(0), None);
+        assert_eq!(dominator_tree.dominator(1), Some(0));
+        // Referenced by both A and B, so only the root dominates it.
+        assert_eq!(dominator_tree.dominator(3), Some(0));
+        assert_eq!(dominator_tree.dominator(4), Some(2));
+        assert_eq!(dominator_tree.dominator(8), Some(6));
+    }
+
+    #[test]
+    fn weakly_referenced_nodes_are_unreachable() {
+        let dominator_tree = dominator_tree();
+
+        assert!(!dominator_tree.is_reachable(7));
+        assert_eq!(dominator_tree.dominator(7), None);
+    }
+
+    #[test]
+    fn retained_sizes() {
+        let dominator_tree = dominator_tree();
+
+        assert_eq!(dominator_tree.retained_size(0), 116);
+        assert_eq!(dominator_tree.retained_size(1), 10);
+        assert_eq!(dominator_tree.retained_size(2), 66);
+        assert_eq!(dominator_tree.retained_size(3), 40);
+        assert_eq!(dominator_tree.retained_size(7), 50);
+    }
+}
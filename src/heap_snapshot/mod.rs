@@ -0,0 +1,4 @@
+pub mod analyze;
+pub mod diff;
+mod dominator_tree;
+pub mod snapshot;
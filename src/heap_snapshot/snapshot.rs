@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+pub type HeapSnapshotResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Deserialize, Debug)]
+struct RawHeapSnapshot {
+    snapshot: RawHeapSnapshotInfo,
+    nodes: Vec<u64>,
+    edges: Vec<u64>,
+    strings: Vec<String>,
+    #[serde(default)]
+    locations: Vec<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RawHeapSnapshotInfo {
+    meta: RawHeapSnapshotMeta,
+}
+
+#[derive(Deserialize, Debug)]
+struct RawHeapSnapshotMeta {
+    node_fields: Vec<String>,
+    node_types: Vec<Value>,
+    edge_fields: Vec<String>,
+    edge_types: Vec<Value>,
+    #[serde(default)]
+    location_fields: Vec<String>,
+}
+
+/// Source position V8 recorded for a node, the constructor of an object or a function.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NodeLocation {
+    pub script_id: u64,
+    pub line_number: u64,
+    pub column_number: u64,
+}
+
+/// A parsed `.heapsnapshot` file. Nodes and edges are kept in the flat arrays of the file
+/// format, a node is addressed by its index (not its offset in the `nodes` array).
+pub struct HeapSnapshot {
+    nodes: Vec<u64>,
+    edges: Vec<u64>,
+    strings: Vec<String>,
+    node_types: Vec<String>,
+    edge_types: Vec<String>,
+    node_field_count: usize,
+    node_type_offset: usize,
+    node_name_offset: usize,
+    node_self_size_offset: usize,
+    edge_field_count: usize,
+    edge_type_offset: usize,
+    edge_name_offset: usize,
+    edge_to_node_offset: usize,
+    first_edges: Vec<usize>,
+    locations: Vec<(usize, NodeLocation)>,
+}
+
+impl HeapSnapshot {
+    pub fn from_file(path: &str) -> HeapSnapshotResult<HeapSnapshot> {
+        let file = std::fs::File::open(path)?;
+        let raw: RawHeapSnapshot = serde_json::from_reader(std::io::BufReader::new(file))?;
+        HeapSnapshot::from_raw(raw)
+    }
+
+    #[cfg(test)]
+    pub fn from_json(json: &str) -> HeapSnapshotResult<HeapSnapshot> {
+        HeapSnapshot::from_raw(serde_json::from_str(json)?)
+    }
+
+    fn from_raw(raw: RawHeapSnapshot) -> HeapSnapshotResult<HeapSnapshot> {
+        let meta = &raw.snapshot.meta;
+        let node_field = |name: &str| field_offset(&meta.node_fields, name);
+        let edge_field = |name: &str| field_offset(&meta.edge_fields, name);
+
+        let node_field_count = meta.node_fields.len();
+        let node_edge_count_offset = node_field("edge_count")?;
+        let edge_field_count = meta.edge_fields.len();
+
+        if node_field_count == 0 || edge_field_count == 0 {
+            return Err("snapshot meta has no node or edge fields".into());
+        }
+
+        // Edges of a node follow the edges of the previous node, so the first edge of each
+        // node is the running sum of the edge counts.
+        let node_count = raw.nodes.len() / node_field_count;
+
+        if node_count == 0 {
+            return Err("snapshot has no nodes".into());
+        }
+
+        let mut first_edges = Vec::with_capacity(node_count + 1);
+        let mut first_edge = 0;
+
+        for node in 0..node_count {
+            first_edges.push(first_edge);
+            first_edge += raw.nodes[node * node_field_count + node_edge_count_offset] as usize;
+        }
+
+        first_edges.push(first_edge);
+
+        if first_edge * edge_field_count > raw.edges.len() {
+            return Err("snapshot has fewer edges than its nodes reference".into());
+        }
+
+        let locations = parse_locations(&raw, node_field_count)?;
+
+        Ok(HeapSnapshot {
+            locations,
+            node_types: type_names(&meta.node_types, &meta.node_fields, "type")?,
+            edge_types: type_names(&meta.edge_types, &meta.edge_fields, "type")?,
+            node_field_count,
+            node_type_offset: node_field("type")?,
+            node_name_offset: node_field("name")?,
+            node_self_size_offset: node_field("self_size")?,
+            edge_field_count,
+            edge_type_offset: edge_field("type")?,
+            edge_name_offset: edge_field("name_or_index")?,
+            edge_to_node_offset: edge_field("to_node")?,
+            first_edges,
+            nodes: raw.nodes,
+            edges: raw.edges,
+            strings: raw.strings,
+        })
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.first_edges.len() - 1
+    }
+
+    pub fn node_type(&self, node: usize) -> &str {
+        let node_type = self.node_field(node, self.node_type_offset) as usize;
+        self.node_types
+            .get(node_type)
+            .map(String::as_str)
+            .unwrap_or("unknown")
+    }
+
+    pub fn node_name(&self, node: usize) -> &str {
+        let name = self.node_field(node, self.node_name_offset) as usize;
+        self.strings.get(name).map(String::as_str).unwrap_or("")
+    }
+
+    pub fn self_size(&self, node: usize) -> u64 {
+        self.node_field(node, self.node_self_size_offset)
+    }
+
+    /// Name under which DevTools groups the node in the summary view.
+    pub fn constructor_name(&self, node: usize) -> String {
+        match self.node_type(node) {
+            "object" | "native" | "synthetic" => self.node_name(node).to_owned(),
+            "hidden" | "object shape" => "(system)".to_owned(),
+            "string" | "concatenated string" | "sliced string" => "(string)".to_owned(),
+            "number" | "heap number" => "(number)".to_owned(),
+            "code" => "(compiled code)".to_owned(),
+            node_type => format!("({})", node_type),
+        }
+    }
+
+    /// Nodes referenced by the node, skipping weak references which don't retain objects.
+    pub fn strong_references(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        (self.first_edges[node]..self.first_edges[node + 1])
+            .map(move |edge| edge * self.edge_field_count)
+            .filter(move |edge| {
+                let edge_type = self.edges[edge + self.edge_type_offset] as usize;
+                self.edge_types.get(edge_type).map(String::as_str) != Some("weak")
+            })
+            .map(move |edge| {
+                self.edges[edge + self.edge_to_node_offset] as usize / self.node_field_count
+            })
+    }
+
+    /// Nodes with a recorded source location.
+    pub fn locations(&self) -> &[(usize, NodeLocation)] {
+        &self.locations
+    }
+
+    /// URLs of the scripts referenced by the locations. The snapshot has no table of scripts,
+    /// but the location of a function is in the script its shared function info points to,
+    /// and script nodes are named after their URL.
+    pub fn script_urls(&self) -> HashMap<u64, String> {
+        let mut script_urls = HashMap::new();
+
+        for (node, location) in &self.locations {
+            if self.node_type(*node) != "closure" || script_urls.contains_key(&location.script_id) {
+                continue;
+            }
+
+            let script = self.internal_reference(*node, "shared").and_then(|shared| {
+                self.internal_reference(shared, "script_or_debug_info")
+                    .or_else(|| self.internal_reference(shared, "script"))
+            });
+
+            if let Some(script) = script {
+                if !self.node_name(script).is_empty() {
+                    script_urls.insert(location.script_id, self.node_name(script).to_owned());
+                }
+            }
+        }
+
+        script_urls
+    }
+
+    /// Node referenced by the node through the internal field with the name.
+    fn internal_reference(&self, node: usize, name: &str) -> Option<usize> {
+        (self.first_edges[node]..self.first_edges[node + 1])
+            .map(|edge| edge * self.edge_field_count)
+            .find(|edge| {
+                let edge_type = self.edges[edge + self.edge_type_offset] as usize;
+                let edge_name = self.edges[edge + self.edge_name_offset] as usize;
+                self.edge_types.get(edge_type).map(String::as_str) == Some("internal")
+                    && self.strings.get(edge_name).map(String::as_str) == Some(name)
+            })
+            .map(|edge| {
+                self.edges[edge + self.edge_to_node_offset] as usize / self.node_field_count
+            })
+    }
+
+    fn node_field(&self, node: usize, offset: usize) -> u64 {
+        self.nodes[node * self.node_field_count + offset]
+    }
+}
+
+fn parse_locations(
+    raw: &RawHeapSnapshot,
+    node_field_count: usize,
+) -> HeapSnapshotResult<Vec<(usize, NodeLocation)>> {
+    let fields = &raw.snapshot.meta.location_fields;
+
+    if fields.is_empty() || raw.locations.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let object_index_offset = field_offset(fields, "object_index")?;
+    let script_id_offset = field_offset(fields, "script_id")?;
+    let line_offset = field_offset(fields, "line")?;
+    let column_offset = field_offset(fields, "column")?;
+
+    // The object index is the offset of the node in the `nodes` array.
+    Ok(raw
+        .locations
+        .chunks_exact(fields.len())
+        .map(|location| {
+            let node = location[object_index_offset] as usize / node_field_count;
+            let node_location = NodeLocation {
+                script_id: location[script_id_offset],
+                line_number: location[line_offset],
+                column_number: location[column_offset],
+            };
+            (node, node_location)
+        })
+        .collect())
+}
+
+fn field_offset(fields: &[String], name: &str) -> HeapSnapshotResult<usize> {
+    fields
+        .iter()
+        .position(|field| field == name)
+        .ok_or_else(|| format!("snapshot meta has no {} field", name).into())
+}
+
+/// The enum values of the `type` field, listed in the meta at the position of the field.
+fn type_names(types: &[Value], fields: &[String], name: &str) -> HeapSnapshotResult<Vec<String>> {
+    let names = types
+        .get(field_offset(fields, name)?)
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("snapshot meta has no values of the {} field", name))?;
+
+    Ok(names
+        .iter()
+        .map(|name| name.as_str().unwrap_or_default().to_owned())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HeapSnapshot, NodeLocation};
+
+    fn snapshot() -> HeapSnapshot {
+        HeapSnapshot::from_json(include_str!("fixtures/graph.heapsnapshot")).unwrap()
+    }
+
+    #[test]
+    fn nodes() {
+        let snapshot = snapshot();
+
+        assert_eq!(snapshot.node_count(), 9);
+        assert_eq!(snapshot.node_type(0), "synthetic");
+        assert_eq!(snapshot.node_name(2), "B");
+        assert_eq!(snapshot.self_size(4), 40);
+        assert_eq!(snapshot.constructor_name(5), "A");
+        assert_eq!(snapshot.constructor_name(4), "(closure)");
+        assert_eq!(snapshot.constructor_name(6), "(compiled code)");
+    }
+
+    #[test]
+    fn strong_references() {
+        let snapshot = snapshot();
+
+        assert_eq!(snapshot.strong_references(0).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(snapshot.strong_references(2).collect::<Vec<_>>(), [3, 4]);
+        assert_eq!(snapshot.strong_references(5).count(), 0);
+    }
+
+    #[test]
+    fn locations() {
+        let snapshot = snapshot();
+
+        assert_eq!(snapshot.locations().len(), 3);
+        assert_eq!(
+            snapshot.locations()[1],
+            (
+                4,
+                NodeLocation {
+                    script_id: 3,
+                    line_number: 1,
+                    column_number: 2,
+                }
+            )
+        );
+        assert_eq!(
+            snapshot.script_urls().get(&3).map(String::as_str),
+            Some("/app.js")
+        );
+    }
+
+    #[test]
+    fn invalid_snapshots() {
+        let fixture = include_str!("fixtures/graph.heapsnapshot");
+
+        let without_self_size = fixture.replace("\"self_size\"", "\"size\"");
+        let error = HeapSnapshot::from_json(&without_self_size).err().unwrap();
+        assert_eq!(error.to_string(), "snapshot meta has no self_size field");
+
+        let with_missing_edges = fixture.replace("3, 11, 40\n", "");
+        assert!(HeapSnapshot::from_json(&with_missing_edges).is_err());
+    }
+}
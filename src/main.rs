@@ -1,9 +1,16 @@
 mod cdt;
+mod color;
+mod config;
+mod heap_snapshot;
 mod repl;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 use crate::cdt::http_client::get_debuggers;
+use crate::color::disable_colors_unless_terminal;
+use crate::config::{Config, DEFAULT_CONFIG_FILE_PATH};
+use crate::heap_snapshot::analyze::analyze_command;
+use crate::heap_snapshot::diff::diff_command;
 use crate::repl::start_repl::start_repl;
 
 #[derive(Parser, Debug)]
@@ -14,10 +21,51 @@ struct Args {
 
     #[clap(short, long, default_value = "9229")]
     port: String,
+
+    /// JSON config file, `.node-debug.json` is used if it exists
+    #[clap(short, long)]
+    config: Option<String>,
+
+    /// REPL command to run every time the debugger stops, added to the configured ones (can be repeated)
+    #[clap(short, long)]
+    display: Vec<String>,
+
+    /// Regular expression of script URLs to blackbox, added to the default ones (can be repeated)
+    #[clap(short, long)]
+    blackbox: Vec<String>,
+
+    /// Run REPL commands from the file and exit, non-zero exit code if a command fails
+    #[clap(long)]
+    commands: Option<String>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Work with heap snapshots without connecting to a debugger
+    #[clap(subcommand)]
+    Heap(HeapCommand),
+}
+
+#[derive(Subcommand, Debug)]
+enum HeapCommand {
+    /// Show constructors retaining the most memory in a .heapsnapshot file
+    Analyze { file: String },
+    /// Show constructors and allocation sites with more instances in the second snapshot
+    Diff { before: String, after: String },
 }
 
 fn main() {
     let args = Args::parse();
+    disable_colors_unless_terminal();
+
+    if let Some(command) = &args.command {
+        run_command(command);
+    }
+
+    let config = load_config(&args);
 
     let debuggers = get_debuggers(&args.host, &args.port);
 
@@ -32,5 +80,50 @@ fn main() {
         }
     };
 
-    start_repl(&args.host, &args.port, &debugger_id);
+    start_repl(
+        &args.host,
+        &args.port,
+        &debugger_id,
+        &config,
+        args.commands.as_deref(),
+    );
+}
+
+fn run_command(command: &Command) -> ! {
+    let result = match command {
+        Command::Heap(HeapCommand::Analyze { file }) => analyze_command(file),
+        Command::Heap(HeapCommand::Diff { before, after }) => diff_command(before, after),
+    };
+
+    match result {
+        Ok(()) => std::process::exit(0),
+        Err(err) => {
+            println!("Error: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn load_config(args: &Args) -> Config {
+    let mut config = match &args.config {
+        Some(path) => Config::from_file(path).unwrap_or_else(|err| {
+            println!("Error while reading config {}: {}", path, err);
+            std::process::exit(1);
+        }),
+        None if std::path::Path::new(DEFAULT_CONFIG_FILE_PATH).exists() => {
+            Config::from_file(DEFAULT_CONFIG_FILE_PATH).unwrap_or_else(|err| {
+                println!(
+                    "Error while reading config {}: {}",
+                    DEFAULT_CONFIG_FILE_PATH, err
+                );
+                std::process::exit(1);
+            })
+        }
+        None => Config::default(),
+    };
+
+    config.display.extend(args.display.iter().cloned());
+    config.blackbox.extend(args.blackbox.iter().cloned());
+
+    config
 }
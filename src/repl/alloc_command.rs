@@ -0,0 +1,153 @@
+use crate::cdt::client::CDTClient;
+use crate::cdt::models::{
+    DebuggerLocation, HeapProfilerSamplingHeapProfile, HeapProfilerSamplingHeapProfileNode,
+    RuntimeCallFrame,
+};
+use crate::color::{colored, GRAY};
+use crate::heap_snapshot::analyze::format_size;
+
+use super::output_file::output_path;
+use super::repl_state::ReplState;
+use super::source_location::format_location;
+
+static TOP_STACKS: usize = 20;
+static MAX_STACK_FRAMES: usize = 5;
+
+pub fn alloc_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let args = line.split_whitespace().skip(1).collect::<Vec<&str>>();
+
+    match args.as_slice() {
+        ["start"] => alloc_start(client, repl_state),
+        ["stop"] => alloc_stop(client, None, repl_state),
+        ["stop", path] => alloc_stop(client, Some(path), repl_state),
+        _ => {
+            println!("Expected alloc start or alloc stop [<file>]");
+            repl_state.fail()
+        }
+    }
+}
+
+fn alloc_start(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    match client.heap_profiler_start_sampling() {
+        Ok(()) => {
+            println!("Allocation sampling started");
+            repl_state
+        }
+        Err(err) => {
+            println!("Error while starting allocation sampling: {}", err);
+            repl_state.fail()
+        }
+    }
+}
+
+fn alloc_stop(client: &mut CDTClient, path: Option<&str>, repl_state: ReplState) -> ReplState {
+    let profile = match client.heap_profiler_stop_sampling() {
+        Ok(profile) => profile,
+        Err(err) => {
+            println!("Error while stopping allocation sampling: {}", err);
+            return repl_state.fail();
+        }
+    };
+
+    let path = output_path(path, "alloc", "heapprofile");
+
+    let write_result = serde_json::to_string(&profile)
+        .map_err(|err| err.to_string())
+        .and_then(|json| std::fs::write(&path, json).map_err(|err| err.to_string()));
+
+    if let Err(err) = write_result {
+        println!("Error while writing {}: {}", path, err);
+        return repl_state.fail();
+    }
+
+    println!("Allocation profile saved to {}", path);
+    print_alloc_summary(client, &profile);
+
+    repl_state
+}
+
+/// Print the call stacks with the most sampled bytes. Only allocations still alive when
+/// sampling stopped are part of the profile, so these are the stacks retaining memory.
+fn print_alloc_summary(client: &mut CDTClient, profile: &HeapProfilerSamplingHeapProfile) {
+    let mut stacks = Vec::new();
+    collect_stacks(&profile.head, &mut Vec::new(), &mut stacks);
+
+    if stacks.is_empty() {
+        println!("No allocations sampled");
+        return;
+    }
+
+    let total_size = stacks.iter().map(|(size, _)| size).sum::<f64>().max(1.0);
+    stacks.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    let header = format!("{:>10} {:>6}  allocation stack", "size", "%");
+    println!("{}", colored(GRAY, &header));
+
+    for (size, frames) in stacks.into_iter().take(TOP_STACKS) {
+        let frames = frames
+            .iter()
+            .rev()
+            .take(MAX_STACK_FRAMES)
+            .map(|call_frame| format_frame(client, call_frame))
+            .collect::<Vec<_>>();
+
+        for (i, frame) in frames.iter().enumerate() {
+            if i == 0 {
+                println!(
+                    "{:>10} {:>5.1}%  {}",
+                    format_size(size as u64),
+                    size / total_size * 100.0,
+                    frame
+                );
+            } else {
+                println!("{:>18}  {}", "", frame);
+            }
+        }
+    }
+}
+
+/// Walk the profile tree, the stack of a node is the path from the root (excluded).
+fn collect_stacks<'a>(
+    node: &'a HeapProfilerSamplingHeapProfileNode,
+    path: &mut Vec<&'a RuntimeCallFrame>,
+    stacks: &mut Vec<(f64, Vec<&'a RuntimeCallFrame>)>,
+) {
+    let is_root = node.call_frame.function_name == "(root)";
+
+    if !is_root {
+        path.push(&node.call_frame);
+
+        if node.self_size > 0.0 {
+            stacks.push((node.self_size, path.clone()));
+        }
+    }
+
+    for child in &node.children {
+        collect_stacks(child, path, stacks);
+    }
+
+    if !is_root {
+        path.pop();
+    }
+}
+
+fn format_frame(client: &mut CDTClient, call_frame: &RuntimeCallFrame) -> String {
+    let function_name = if call_frame.function_name.is_empty() {
+        "(anonymous)"
+    } else {
+        &call_frame.function_name
+    };
+
+    if call_frame.url.is_empty() {
+        return function_name.to_owned();
+    }
+
+    let location = DebuggerLocation {
+        script_id: call_frame.script_id.to_owned(),
+        line_number: call_frame.line_number.max(0) as u32,
+        column_number: call_frame.column_number.max(0) as u32,
+    };
+
+    let location = format!("({})", format_location(client, &location));
+    format!("{} {}", function_name, colored(GRAY, &location))
+}
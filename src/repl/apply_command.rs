@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use crate::cdt::client::{CDTClient, CDTClientResult};
+use crate::cdt::models::{DebuggerSetScriptSourceResult, RuntimeScriptId};
+
+use super::repl_state::{ReplState, ReplStateCallFrame};
+
+pub fn apply_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let path = line
+        .split_once(' ')
+        .map(|(_, path)| path.trim())
+        .unwrap_or("");
+
+    if path.is_empty() {
+        println!("Expected path to the edited file");
+        return repl_state.fail();
+    }
+
+    let script_source = match std::fs::read_to_string(path) {
+        Ok(script_source) => script_source,
+        Err(err) => {
+            println!("Error while reading {}: {}", path, err);
+            return repl_state.fail();
+        }
+    };
+
+    let (script_id, url) = match find_script(client, path) {
+        Ok(script) => script,
+        Err(err) => {
+            println!("Error: {}", err);
+            return repl_state.fail();
+        }
+    };
+
+    let dry_run_result = client.debugger_set_script_source(script_id.clone(), &script_source, true);
+
+    if !check_set_script_source_result(path, dry_run_result) {
+        return repl_state.fail();
+    }
+
+    let result = client.debugger_set_script_source(script_id, &script_source, false);
+
+    match result {
+        Ok(result) if result.is_ok() => {
+            println!("Applied {} to {}", path, url);
+
+            match result.call_frames {
+                Some(call_frames) => ReplState {
+                    call_frames: Some(ReplStateCallFrame {
+                        call_frames,
+                        active_id: 0,
+                        async_stack_trace: None,
+                        async_stack_trace_id: None,
+                    }),
+                    ..repl_state
+                },
+                None => repl_state,
+            }
+        }
+        result => {
+            check_set_script_source_result(path, result);
+            repl_state.fail()
+        }
+    }
+}
+
+/// Print why the script source cannot be applied, returns `true` if it can.
+fn check_set_script_source_result(
+    path: &str,
+    result: CDTClientResult<DebuggerSetScriptSourceResult>,
+) -> bool {
+    let result = match result {
+        Ok(result) => result,
+        Err(err) => {
+            println!("Error while applying {}: {}", path, err);
+            return false;
+        }
+    };
+
+    if let Some(exception_details) = &result.exception_details {
+        println!(
+            "Compile error at {}:{}:{}: {}",
+            path,
+            exception_details.line_number + 1,
+            exception_details.column_number + 1,
+            exception_details.text
+        );
+        return false;
+    }
+
+    match result.status.as_deref() {
+        None | Some("Ok") => true,
+        Some(status) => {
+            println!("Error while applying {}: {}", path, status);
+            false
+        }
+    }
+}
+
+/// Find the loaded script for a local file, either by its `file://` URL or by the
+/// longest URL suffix matching the path when the file was loaded from elsewhere.
+fn find_script(client: &CDTClient, path: &str) -> Result<(RuntimeScriptId, String), String> {
+    let file_url = Path::new(path)
+        .canonicalize()
+        .map(|path| format!("file://{}", path.display()))
+        .unwrap_or_default();
+
+    let scripts = client.scripts().values();
+
+    if let Some(script) = scripts.clone().find(|script| script.url == file_url) {
+        return Ok((script.script_id.to_owned(), script.url.to_owned()));
+    }
+
+    let path_suffix = format!("/{}", path.trim_start_matches("./"));
+    let matching_scripts = scripts
+        .filter(|script| script.url.ends_with(&path_suffix))
+        .collect::<Vec<_>>();
+
+    match matching_scripts.as_slice() {
+        [script] => Ok((script.script_id.to_owned(), script.url.to_owned())),
+        [] => Err(format!("no loaded script matches {}", path)),
+        scripts => Err(format!(
+            "{} matches multiple scripts: {}",
+            path,
+            scripts
+                .iter()
+                .map(|script| script.url.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )),
+    }
+}
@@ -0,0 +1,94 @@
+use crate::cdt::client::CDTClient;
+use crate::cdt::models::{DebuggerLocation, DebuggerPausedCallFrame, RuntimeStackTrace};
+
+use super::handle_pause_of_destry_message::handle_pause_or_destroy_message;
+use super::repl_state::{DebuggerState, ReplState};
+
+static MAX_ASYNC_NEXT_STEPS: usize = 1000;
+
+/// Step over until the debugger stops in the same invocation of the function again, so
+/// stepping over an `await` skips the unrelated code running until it is resumed, including
+/// other calls of the same function.
+pub fn async_next_command(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    if !matches!(repl_state.debugger_state, DebuggerState::Paused) {
+        println!("Error: debugger is not paused");
+        return repl_state.fail();
+    }
+
+    let (function_location, is_returning) = match repl_state.get_active_call_frame() {
+        Some(call_frame) => (
+            call_frame.function_location.clone(),
+            call_frame.return_value.is_some(),
+        ),
+        None => {
+            println!("Error: no active call frame");
+            return repl_state.fail();
+        }
+    };
+
+    let invocation_callers = match &repl_state.call_frames {
+        Some(call_frames) => callers(
+            &call_frames.call_frames,
+            call_frames.async_stack_trace.as_ref(),
+        ),
+        None => Vec::new(),
+    };
+
+    let mut message = None;
+
+    for _ in 0..MAX_ASYNC_NEXT_STEPS {
+        client.debugger_step_over().unwrap();
+        message = client.runtime_run_if_waiting_for_debugger().unwrap();
+
+        let is_in_same_invocation = message.as_ref().is_some_and(|message| {
+            message
+                .params
+                .call_frames
+                .first()
+                .is_some_and(|call_frame| call_frame.function_location == function_location)
+                && callers(
+                    &message.params.call_frames,
+                    message.params.async_stack_trace.as_ref(),
+                ) == invocation_callers
+        });
+
+        // Stepping over the return leaves the invocation for good, like `next` does.
+        if message.is_none() || is_in_same_invocation || is_returning {
+            return handle_pause_or_destroy_message(client, message, repl_state);
+        }
+    }
+
+    println!(
+        "Error: the function was not resumed within {} steps",
+        MAX_ASYNC_NEXT_STEPS
+    );
+    handle_pause_or_destroy_message(client, message, repl_state).fail()
+}
+
+/// Identify an invocation by where it was called from: the locations of the calling frames,
+/// followed by the async stack. Once resumed after an `await` the calling frames are gone
+/// and the callers which awaited the invocation are in the async stack instead, at the same
+/// locations.
+fn callers(
+    call_frames: &[DebuggerPausedCallFrame],
+    async_stack_trace: Option<&RuntimeStackTrace>,
+) -> Vec<DebuggerLocation> {
+    let mut callers = call_frames
+        .iter()
+        .skip(1)
+        .map(|call_frame| call_frame.location.clone())
+        .collect::<Vec<_>>();
+
+    let mut stack_trace = async_stack_trace;
+
+    while let Some(trace) = stack_trace {
+        callers.extend(trace.call_frames.iter().map(|call_frame| DebuggerLocation {
+            script_id: call_frame.script_id.to_owned(),
+            line_number: call_frame.line_number.max(0) as u32,
+            column_number: call_frame.column_number.max(0) as u32,
+        }));
+        stack_trace = trace.parent.as_deref();
+    }
+
+    callers
+}
@@ -0,0 +1,140 @@
+use regex::Regex;
+
+use crate::cdt::client::CDTClient;
+use crate::cdt::models::{RuntimeStackTrace, RuntimeStackTraceId};
+use crate::color::{colored, GRAY};
+
+use super::blackbox_command::is_blackboxed;
+use super::repl_state::ReplState;
+
+pub fn backtrace_command(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    let call_frames = match &repl_state.call_frames {
+        Some(call_frames) => call_frames,
+        None => {
+            println!("Error: no active call frame");
+            return repl_state.fail();
+        }
+    };
+
+    let blackbox_patterns = &repl_state.blackbox_patterns;
+    let mut blackboxed_frames = 0;
+
+    for (i, call_frame) in call_frames.call_frames.iter().enumerate() {
+        let url = client
+            .scripts()
+            .get(&call_frame.location.script_id)
+            .map(|script| script.url.as_str())
+            .filter(|url| !url.is_empty())
+            .unwrap_or(&call_frame.location.script_id);
+
+        if i != call_frames.active_id && is_blackboxed(url, blackbox_patterns) {
+            blackboxed_frames += 1;
+            continue;
+        }
+
+        print_blackboxed_frames(&mut blackboxed_frames, "   ");
+
+        let marker = if i == call_frames.active_id { ">" } else { " " };
+        println!(
+            "{} #{} {}",
+            marker,
+            i,
+            format_frame(
+                &call_frame.function_name,
+                url,
+                call_frame.location.line_number as i32,
+                call_frame.location.column_number as i32
+            )
+        );
+    }
+
+    print_blackboxed_frames(&mut blackboxed_frames, "   ");
+
+    let mut async_stack_trace = match (
+        &call_frames.async_stack_trace,
+        &call_frames.async_stack_trace_id,
+    ) {
+        (Some(async_stack_trace), _) => Some(async_stack_trace.clone()),
+        (None, Some(async_stack_trace_id)) => get_stack_trace(client, async_stack_trace_id.clone()),
+        (None, None) => None,
+    };
+
+    while let Some(stack_trace) = async_stack_trace {
+        print_async_stack_trace(&stack_trace, blackbox_patterns);
+
+        async_stack_trace = match (stack_trace.parent, stack_trace.parent_id) {
+            (Some(parent), _) => Some(*parent),
+            (None, Some(parent_id)) => get_stack_trace(client, parent_id),
+            (None, None) => None,
+        };
+    }
+
+    repl_state
+}
+
+fn print_async_stack_trace(stack_trace: &RuntimeStackTrace, blackbox_patterns: &[Regex]) {
+    let description = stack_trace.description.as_deref().unwrap_or("async");
+    println!("   {}", colored(GRAY, &format!("-- {} --", description)));
+
+    let mut blackboxed_frames = 0;
+
+    for call_frame in &stack_trace.call_frames {
+        if is_blackboxed(&call_frame.url, blackbox_patterns) {
+            blackboxed_frames += 1;
+            continue;
+        }
+
+        print_blackboxed_frames(&mut blackboxed_frames, "      ");
+
+        println!(
+            "      {}",
+            format_frame(
+                &call_frame.function_name,
+                &call_frame.url,
+                call_frame.line_number,
+                call_frame.column_number
+            )
+        );
+    }
+
+    print_blackboxed_frames(&mut blackboxed_frames, "      ");
+}
+
+/// Print a single line in place of consecutive blackboxed frames and reset the counter.
+fn print_blackboxed_frames(blackboxed_frames: &mut usize, indent: &str) {
+    match *blackboxed_frames {
+        0 => return,
+        1 => println!("{}{}", indent, colored(GRAY, "... 1 blackboxed frame")),
+        n => println!(
+            "{}{}",
+            indent,
+            colored(GRAY, &format!("... {} blackboxed frames", n))
+        ),
+    }
+
+    *blackboxed_frames = 0;
+}
+
+fn get_stack_trace(
+    client: &mut CDTClient,
+    stack_trace_id: RuntimeStackTraceId,
+) -> Option<RuntimeStackTrace> {
+    match client.debugger_get_stack_trace(stack_trace_id) {
+        Ok(stack_trace) => Some(stack_trace),
+        Err(err) => {
+            println!("Error while getting async stack trace: {}", err);
+            None
+        }
+    }
+}
+
+fn format_frame(function_name: &str, url: &str, line_number: i32, column_number: i32) -> String {
+    let function_name = if function_name.is_empty() {
+        "(anonymous)"
+    } else {
+        function_name
+    };
+
+    let location = format!("({}:{}:{})", url, line_number + 1, column_number + 1);
+    format!("{} {}", function_name, colored(GRAY, &location))
+}
@@ -0,0 +1,111 @@
+use regex::Regex;
+
+use crate::cdt::client::{CDTClient, CDTClientResult};
+
+use super::repl_state::ReplState;
+
+pub fn blackbox_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let pattern = line.split_once(' ').map(|(_, pattern)| pattern.trim());
+
+    let pattern = match pattern {
+        Some(pattern) if !pattern.is_empty() => pattern,
+        _ => return list_blackbox_patterns(repl_state),
+    };
+
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(err) => {
+            println!("Invalid pattern {}: {}", pattern, err);
+            return repl_state.fail();
+        }
+    };
+
+    let mut blackbox_patterns = repl_state.blackbox_patterns.clone();
+    blackbox_patterns.push(regex);
+
+    if let Err(err) = set_blackbox_patterns(client, &blackbox_patterns) {
+        println!("Error while blackboxing {}: {}", pattern, err);
+        return repl_state.fail();
+    }
+
+    println!(
+        "Blackbox pattern #{} added: {}",
+        blackbox_patterns.len(),
+        pattern
+    );
+
+    ReplState {
+        blackbox_patterns,
+        ..repl_state
+    }
+}
+
+/// Remove the n-th blackbox pattern, or all of them when no number is given.
+pub fn unblackbox_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let number_string = line.split_once(' ').map(|(_, n)| n.trim()).unwrap_or("");
+    let mut blackbox_patterns = repl_state.blackbox_patterns.clone();
+
+    if number_string.is_empty() {
+        blackbox_patterns.clear();
+    } else {
+        let number: Option<usize> = number_string.parse().ok();
+
+        match number {
+            Some(number) if number > 0 && number <= blackbox_patterns.len() => {
+                blackbox_patterns.remove(number - 1);
+            }
+            _ => {
+                println!("Expected blackbox pattern number, got {}", number_string);
+                return repl_state.fail();
+            }
+        }
+    }
+
+    if let Err(err) = set_blackbox_patterns(client, &blackbox_patterns) {
+        println!("Error while updating blackbox patterns: {}", err);
+        return repl_state.fail();
+    }
+
+    if number_string.is_empty() {
+        println!("All blackbox patterns removed");
+    } else {
+        println!("Blackbox pattern #{} removed", number_string);
+    }
+
+    ReplState {
+        blackbox_patterns,
+        ..repl_state
+    }
+}
+
+fn list_blackbox_patterns(repl_state: ReplState) -> ReplState {
+    if repl_state.blackbox_patterns.is_empty() {
+        println!("No blackbox patterns.");
+    }
+
+    for (i, pattern) in repl_state.blackbox_patterns.iter().enumerate() {
+        println!(" {}: {}", i + 1, pattern);
+    }
+
+    repl_state
+}
+
+/// Compile the configured patterns once, they are matched against every frame and script.
+pub fn compile_blackbox_patterns(patterns: &[String]) -> Result<Vec<Regex>, regex::Error> {
+    patterns.iter().map(|pattern| Regex::new(pattern)).collect()
+}
+
+pub fn set_blackbox_patterns(client: &mut CDTClient, patterns: &[Regex]) -> CDTClientResult<()> {
+    let patterns = patterns
+        .iter()
+        .map(|pattern| pattern.as_str().to_owned())
+        .collect::<Vec<String>>();
+    client.debugger_set_blackbox_patterns(&patterns)
+}
+
+/// Check whether the script URL matches any of the blackbox patterns.
+pub fn is_blackboxed(url: &str, blackbox_patterns: &[Regex]) -> bool {
+    blackbox_patterns
+        .iter()
+        .any(|pattern| pattern.is_match(url))
+}
@@ -0,0 +1,215 @@
+use crate::cdt::client::CDTClient;
+
+use super::render_remote_object::runtime_remote_object_to_string;
+use super::repl_state::{ReplState, ReplStateBreakpoint};
+
+pub fn breakpoint_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let location = line.split_once(' ').map(|(_, location)| location.trim());
+    let parsed_location = location
+        .and_then(|location| location.rsplit_once(':'))
+        .and_then(|(url, line_number)| Some((url, line_number.parse::<u32>().ok()?)))
+        .filter(|(url, line_number)| !url.is_empty() && *line_number > 0);
+
+    if parsed_location.is_none() {
+        println!("Expected <url>:<line>, got {}", location.unwrap_or(""));
+        return repl_state.fail();
+    }
+
+    let (url, line_number) = parsed_location.unwrap();
+    let is_known_url = client.scripts().values().any(|script| script.url == url);
+
+    // Partial paths are matched as a suffix of the script URL so `b app.js:10` works
+    // without typing the whole `file://` URL.
+    let result = if is_known_url {
+        client.debugger_set_breakpoint_by_url(url, line_number - 1)
+    } else {
+        let url_regex = format!("{}$", escape_regex(url));
+        client.debugger_set_breakpoint_by_url_regex(&url_regex, line_number - 1)
+    };
+
+    match result {
+        Ok(result) => {
+            let id = next_breakpoint_id(&repl_state);
+
+            println!(
+                "Breakpoint #{} set at {}:{} ({} locations)",
+                id,
+                url,
+                line_number,
+                result.locations.len()
+            );
+
+            let mut breakpoints = repl_state.breakpoints.clone();
+            breakpoints.push(ReplStateBreakpoint {
+                id,
+                breakpoint_id: result.breakpoint_id,
+                url: url.to_owned(),
+                line_number,
+                function: None,
+            });
+
+            ReplState {
+                breakpoints,
+                ..repl_state
+            }
+        }
+        Err(err) => {
+            println!("Error while setting breakpoint: {}", err);
+            repl_state.fail()
+        }
+    }
+}
+
+/// Break when the function the expression evaluates to is called. Falls back to a breakpoint
+/// at the function's `[[FunctionLocation]]` when V8 doesn't support function call breakpoints.
+pub fn function_breakpoint_command(
+    client: &mut CDTClient,
+    line: &str,
+    repl_state: ReplState,
+) -> ReplState {
+    let expression = line
+        .split_once(' ')
+        .map(|(_, expression)| expression.trim())
+        .unwrap_or("");
+
+    if expression.is_empty() {
+        println!("Expected expression evaluating to a function");
+        return repl_state.fail();
+    }
+
+    let call_frame_id = match repl_state.get_active_call_frame() {
+        Some(call_frame) => call_frame.call_frame_id.to_owned(),
+        None => {
+            println!("Error: no active call frame");
+            return repl_state.fail();
+        }
+    };
+
+    let function = match client.debugger_evaluate_on_call_frame(call_frame_id, expression) {
+        Ok(remote_object) if remote_object.exception_details.is_none() => remote_object.result,
+        Ok(remote_object) => {
+            println!(
+                "Error while evaluating: {}",
+                runtime_remote_object_to_string(remote_object.result)
+            );
+            return repl_state.fail();
+        }
+        Err(err) => {
+            println!("Error while evaluating: {}", err);
+            return repl_state.fail();
+        }
+    };
+
+    let object_id = match function.object_id {
+        Some(object_id) if function.r#type == "function" => object_id,
+        _ => {
+            println!("Error: {} is not a function", expression);
+            return repl_state.fail();
+        }
+    };
+
+    let location = match client.runtime_get_function_location(object_id.to_owned()) {
+        Ok(location) => location,
+        Err(err) => {
+            println!("Error while resolving the function location: {}", err);
+            return repl_state.fail();
+        }
+    };
+
+    let breakpoint_id = match client.debugger_set_breakpoint_on_function_call(object_id) {
+        Ok(result) => Ok(result.breakpoint_id),
+        Err(err) => match &location {
+            Some(location) => client
+                .debugger_set_breakpoint(location)
+                .map(|result| result.breakpoint_id),
+            None => Err(err),
+        },
+    };
+
+    let breakpoint_id = match breakpoint_id {
+        Ok(breakpoint_id) => breakpoint_id,
+        Err(err) => {
+            println!("Error while setting breakpoint: {}", err);
+            return repl_state.fail();
+        }
+    };
+
+    let (url, line_number) = match &location {
+        Some(location) => (
+            client
+                .scripts()
+                .get(&location.script_id)
+                .map(|script| script.url.to_owned())
+                .unwrap_or_else(|| location.script_id.to_owned()),
+            location.line_number + 1,
+        ),
+        None => (String::new(), 0),
+    };
+
+    let breakpoint = ReplStateBreakpoint {
+        id: next_breakpoint_id(&repl_state),
+        breakpoint_id,
+        url,
+        line_number,
+        function: Some(expression.to_owned()),
+    };
+
+    println!(
+        "Breakpoint #{} set on {}",
+        breakpoint.id,
+        format_breakpoint(&breakpoint)
+    );
+
+    let mut breakpoints = repl_state.breakpoints.clone();
+    breakpoints.push(breakpoint);
+
+    ReplState {
+        breakpoints,
+        ..repl_state
+    }
+}
+
+fn next_breakpoint_id(repl_state: &ReplState) -> usize {
+    repl_state
+        .breakpoints
+        .iter()
+        .map(|breakpoint| breakpoint.id)
+        .max()
+        .unwrap_or(0)
+        + 1
+}
+
+fn format_breakpoint(breakpoint: &ReplStateBreakpoint) -> String {
+    match &breakpoint.function {
+        Some(function) if breakpoint.url.is_empty() => function.to_owned(),
+        Some(function) => format!(
+            "{} ({}:{})",
+            function, breakpoint.url, breakpoint.line_number
+        ),
+        None => format!("{}:{}", breakpoint.url, breakpoint.line_number),
+    }
+}
+
+fn escape_regex(input: &str) -> String {
+    input
+        .chars()
+        .map(|c| match c {
+            '\\' | '.' | '+' | '*' | '?' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$' => {
+                format!("\\{}", c)
+            }
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+pub fn list_breakpoints_command(repl_state: ReplState) -> ReplState {
+    if repl_state.breakpoints.is_empty() {
+        println!("No breakpoints set.");
+    }
+
+    for breakpoint in &repl_state.breakpoints {
+        println!("#{} {}", breakpoint.id, format_breakpoint(breakpoint));
+    }
+
+    repl_state
+}
@@ -1,13 +1,14 @@
 use std::str::Lines;
 
 use crate::cdt::models::DebuggerPausedCallFrame;
+use crate::cdt::source_code::SourceCode;
 
-use super::source_code::SourceCode;
+pub type CodePreview = (String, Vec<(usize, String)>);
 
 pub fn create_code_preview(
     source_code: &SourceCode,
     call_frame: &DebuggerPausedCallFrame,
-) -> Result<(String, Vec<(usize, String)>), Box<dyn std::error::Error>> {
+) -> Result<CodePreview, Box<dyn std::error::Error>> {
     let source_mapping = source_code
         .source_mapping
         .as_ref()
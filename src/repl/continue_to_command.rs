@@ -9,7 +9,7 @@ pub fn continue_to_command(client: &mut CDTClient, line: &str, repl_state: ReplS
 
     if number.is_none() {
         println!("Expected number, got {}", number_string);
-        return repl_state;
+        return repl_state.fail();
     }
 
     let number = number.unwrap();
@@ -20,7 +20,7 @@ pub fn continue_to_command(client: &mut CDTClient, line: &str, repl_state: ReplS
 
     if script_id.is_none() {
         println!("Error: no active frame.");
-        return repl_state;
+        return repl_state.fail();
     }
 
     let script_id = script_id.unwrap();
@@ -28,5 +28,5 @@ pub fn continue_to_command(client: &mut CDTClient, line: &str, repl_state: ReplS
     let message = client
         .debugger_continue_to_location(script_id.to_owned(), number)
         .unwrap();
-    handle_pause_or_destroy_message(message)
+    handle_pause_or_destroy_message(client, message, repl_state)
 }
@@ -0,0 +1,317 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use regex::Regex;
+
+use crate::cdt::client::CDTClient;
+use crate::cdt::models::{ProfilerCoverageRange, ProfilerScriptCoverage};
+use crate::cdt::source_code::SourceCode;
+use crate::color::{colored, GRAY};
+
+use super::blackbox_command::is_blackboxed;
+use super::repl_state::ReplState;
+
+pub fn coverage_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let args = line.split_whitespace().skip(1).collect::<Vec<&str>>();
+
+    match args.as_slice() {
+        ["start"] => coverage_start(client, repl_state),
+        ["stop", path] => coverage_stop(client, path, repl_state),
+        _ => {
+            println!("Expected coverage start or coverage stop <file>");
+            repl_state.fail()
+        }
+    }
+}
+
+fn coverage_start(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    match client.profiler_start_precise_coverage() {
+        Ok(()) => {
+            println!("Coverage collection started");
+            repl_state
+        }
+        Err(err) => {
+            println!("Error while starting coverage collection: {}", err);
+            repl_state.fail()
+        }
+    }
+}
+
+fn coverage_stop(client: &mut CDTClient, path: &str, repl_state: ReplState) -> ReplState {
+    let scripts = match client.profiler_take_precise_coverage() {
+        Ok(scripts) => scripts,
+        Err(err) => {
+            println!("Error while taking coverage: {}", err);
+            return repl_state.fail();
+        }
+    };
+
+    if let Err(err) = client.profiler_stop_precise_coverage() {
+        println!("Error while stopping coverage collection: {}", err);
+        return repl_state.fail();
+    }
+
+    let mut files: BTreeMap<String, FileCoverage> = BTreeMap::new();
+
+    for script in &scripts {
+        if script.url.is_empty() || is_blackboxed(&script.url, &repl_state.blackbox_patterns) {
+            continue;
+        }
+
+        if let Ok(source) = client.debugger_get_script_source(script.script_id.to_owned()) {
+            add_script_coverage(
+                &mut files,
+                script,
+                &source.result.script_source,
+                &repl_state.blackbox_patterns,
+            );
+        }
+    }
+
+    if let Err(err) = std::fs::write(path, to_lcov(&files)) {
+        println!("Error while writing {}: {}", path, err);
+        return repl_state.fail();
+    }
+
+    print_coverage_summary(&files);
+    println!("LCOV report saved to {}", path);
+
+    repl_state
+}
+
+/// Execution counts by 1-based line number, and counts of the functions by their line and
+/// name, as functions with the same name (or no name) can be declared on several lines.
+#[derive(Default)]
+struct FileCoverage {
+    lines: BTreeMap<u32, u64>,
+    functions: BTreeMap<(u32, String), u64>,
+}
+
+impl FileCoverage {
+    fn add_line(&mut self, line_number: u32, count: u64) {
+        let line_count = self.lines.entry(line_number).or_default();
+        *line_count = (*line_count).max(count);
+    }
+
+    fn add_function(&mut self, name: &str, line_number: u32, count: u64) {
+        let function_count = self
+            .functions
+            .entry((line_number, name.to_owned()))
+            .or_default();
+        *function_count = (*function_count).max(count);
+    }
+
+    /// Functions with the unique names LCOV identifies them by, anonymous functions and
+    /// functions sharing a name are told apart by their line.
+    fn lcov_functions(&self) -> Vec<(u32, String, u64)> {
+        let mut name_counts: BTreeMap<&str, usize> = BTreeMap::new();
+
+        for (_, name) in self.functions.keys() {
+            *name_counts.entry(name.as_str()).or_default() += 1;
+        }
+
+        self.functions
+            .iter()
+            .map(|((line_number, name), count)| {
+                let lcov_name = if name.is_empty() {
+                    format!("(anonymous_{})", line_number)
+                } else if name_counts[name.as_str()] > 1 {
+                    format!("{}_{}", name, line_number)
+                } else {
+                    name.to_owned()
+                };
+
+                (*line_number, lcov_name, *count)
+            })
+            .collect()
+    }
+
+    fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|count| **count > 0).count()
+    }
+}
+
+/// A non-blank line of a script, with the position of its first character in UTF-16 code
+/// units as used by V8 for both coverage offsets and source map columns.
+struct ScriptLine {
+    line_number: u32,
+    column_number: u32,
+    offset: usize,
+}
+
+/// Convert the ranges of a script into line counts. Ranges of a script are nested, the count
+/// of a line is the count of the innermost range containing its first character. Lines are
+/// attributed to the original sources when the script has a source map.
+fn add_script_coverage(
+    files: &mut BTreeMap<String, FileCoverage>,
+    script: &ProfilerScriptCoverage,
+    script_source: &str,
+    blackbox_patterns: &[Regex],
+) {
+    let source_code = SourceCode::from_str(script_source);
+    let generated_path = script
+        .url
+        .strip_prefix("file://")
+        .unwrap_or(&script.url)
+        .to_owned();
+
+    // Positions without a mapping on the same generated line (comments, the source map
+    // comment itself) don't belong to any original line.
+    let original_position = |line_number: u32, column_number: u32| match &source_code.source_mapping
+    {
+        Some(source_mapping) => {
+            let token = source_mapping.lookup_token(line_number, column_number)?;
+
+            if token.get_dst_line() != line_number {
+                return None;
+            }
+
+            Some((token.get_source()?.to_owned(), token.get_src_line()))
+        }
+        None => Some((generated_path.to_owned(), line_number)),
+    };
+
+    let ranges = script
+        .functions
+        .iter()
+        .flat_map(|function| function.ranges.iter())
+        .collect::<Vec<_>>();
+
+    let lines = script_lines(script_source);
+
+    for line in &lines {
+        let count = match innermost_range(&ranges, line.offset) {
+            Some(range) => range.count,
+            None => continue,
+        };
+
+        if let Some((path, line_number)) = original_position(line.line_number, line.column_number) {
+            if !is_blackboxed(&path, blackbox_patterns) {
+                files
+                    .entry(path)
+                    .or_default()
+                    .add_line(line_number + 1, count);
+            }
+        }
+    }
+
+    for function in &script.functions {
+        // The anonymous function spanning the whole script is the script itself.
+        let range = match function.ranges.first() {
+            Some(range) if !function.function_name.is_empty() || range.start_offset > 0 => range,
+            _ => continue,
+        };
+
+        let line_index = lines
+            .partition_point(|line| line.offset <= range.start_offset)
+            .saturating_sub(1);
+
+        if let Some(line) = lines.get(line_index) {
+            if let Some((path, line_number)) =
+                original_position(line.line_number, line.column_number)
+            {
+                if !is_blackboxed(&path, blackbox_patterns) {
+                    files.entry(path).or_default().add_function(
+                        &function.function_name,
+                        line_number + 1,
+                        range.count,
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn script_lines(script_source: &str) -> Vec<ScriptLine> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+
+    for (line_number, line) in script_source.split('\n').enumerate() {
+        let indentation = line.len() - line.trim_start().len();
+        let column_number = line[..indentation].encode_utf16().count();
+
+        if !line.trim().is_empty() {
+            lines.push(ScriptLine {
+                line_number: line_number as u32,
+                column_number: column_number as u32,
+                offset: offset + column_number,
+            });
+        }
+
+        offset += line.encode_utf16().count() + 1;
+    }
+
+    lines
+}
+
+fn innermost_range<'a>(
+    ranges: &[&'a ProfilerCoverageRange],
+    offset: usize,
+) -> Option<&'a ProfilerCoverageRange> {
+    ranges
+        .iter()
+        .filter(|range| range.start_offset <= offset && offset < range.end_offset)
+        .min_by_key(|range| range.end_offset - range.start_offset)
+        .copied()
+}
+
+fn to_lcov(files: &BTreeMap<String, FileCoverage>) -> String {
+    let mut lcov = String::new();
+
+    for (path, file) in files {
+        let _ = writeln!(lcov, "TN:");
+        let _ = writeln!(lcov, "SF:{}", path);
+
+        let functions = file.lcov_functions();
+
+        for (line_number, name, _) in &functions {
+            let _ = writeln!(lcov, "FN:{},{}", line_number, name);
+        }
+
+        for (_, name, count) in &functions {
+            let _ = writeln!(lcov, "FNDA:{},{}", count, name);
+        }
+
+        let functions_hit = functions.iter().filter(|(_, _, count)| *count > 0).count();
+        let _ = writeln!(lcov, "FNF:{}", functions.len());
+        let _ = writeln!(lcov, "FNH:{}", functions_hit);
+
+        for (line_number, count) in &file.lines {
+            let _ = writeln!(lcov, "DA:{},{}", line_number, count);
+        }
+
+        let _ = writeln!(lcov, "LF:{}", file.lines.len());
+        let _ = writeln!(lcov, "LH:{}", file.lines_hit());
+        let _ = writeln!(lcov, "end_of_record");
+    }
+
+    lcov
+}
+
+fn print_coverage_summary(files: &BTreeMap<String, FileCoverage>) {
+    if files.is_empty() {
+        println!("No coverage collected");
+        return;
+    }
+
+    let header = format!("{:>8} {:>8} {:>6}  file", "lines", "hit", "%");
+    println!("{}", colored(GRAY, &header));
+
+    for (path, file) in files {
+        print_coverage_row(file.lines.len(), file.lines_hit(), path);
+    }
+
+    let total_lines = files.values().map(|file| file.lines.len()).sum();
+    let total_hit = files.values().map(FileCoverage::lines_hit).sum();
+    print_coverage_row(total_lines, total_hit, "total");
+}
+
+fn print_coverage_row(lines: usize, hit: usize, name: &str) {
+    println!(
+        "{:>8} {:>8} {:>5.1}%  {}",
+        lines,
+        hit,
+        hit as f64 / lines.max(1) as f64 * 100.0,
+        name
+    );
+}
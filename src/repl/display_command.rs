@@ -0,0 +1,79 @@
+use crate::cdt::client::CDTClient;
+
+use super::repl_state::ReplState;
+use super::start_repl::run_command;
+
+pub fn display_command(line: &str, repl_state: ReplState) -> ReplState {
+    let command = line.split_once(' ').map(|(_, command)| command.trim());
+
+    let command = match command {
+        Some(command) if !command.is_empty() => command,
+        _ => {
+            list_display_commands(&repl_state);
+            return repl_state;
+        }
+    };
+
+    let mut display = repl_state.display.clone();
+    display.push(command.to_owned());
+    println!("Display #{} added: {}", display.len(), command);
+
+    ReplState {
+        display,
+        ..repl_state
+    }
+}
+
+pub fn undisplay_command(line: &str, repl_state: ReplState) -> ReplState {
+    let number_string = line.split_once(' ').map(|(_, n)| n.trim()).unwrap_or("");
+    let number: Option<usize> = number_string.parse().ok();
+
+    let index = match number {
+        Some(number) if number > 0 && number <= repl_state.display.len() => number - 1,
+        _ => {
+            println!("Expected display number, got {}", number_string);
+            return repl_state.fail();
+        }
+    };
+
+    let mut display = repl_state.display.clone();
+    let command = display.remove(index);
+    println!("Display #{} removed: {}", index + 1, command);
+
+    ReplState {
+        display,
+        ..repl_state
+    }
+}
+
+/// Run the configured display commands after the debugger stopped. Display commands are
+/// disabled while they run so a stepping command in the list cannot recurse, and their
+/// failures do not count as a failure of the command which stopped the debugger.
+pub fn run_display_commands(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    let display = repl_state.display.clone();
+    let command_failed = repl_state.command_failed;
+    let mut repl_state = ReplState {
+        display: Vec::new(),
+        ..repl_state
+    };
+
+    for command in &display {
+        repl_state = run_command(client, command, repl_state);
+    }
+
+    ReplState {
+        display,
+        command_failed,
+        ..repl_state
+    }
+}
+
+fn list_display_commands(repl_state: &ReplState) {
+    if repl_state.display.is_empty() {
+        println!("No display commands.");
+    }
+
+    for (i, command) in repl_state.display.iter().enumerate() {
+        println!(" {}: {}", i + 1, command);
+    }
+}
@@ -1,34 +1,143 @@
-use crate::cdt::client::CDTClient;
-use crate::cdt::models::{RuntimeRemoteObjectResult, RuntimeRemoteObjectResultValue};
+use std::sync::OnceLock;
+use std::time::Duration;
 
-use super::repl_state::ReplState;
+use regex::Regex;
+
+use crate::cdt::client::{CDTClient, CDTClientResult};
+use crate::cdt::models::{DebuggerCallFrameId, RuntimeExecutionContextId, RuntimeRemoteObject};
+use crate::color::{colored, GRAY};
+
+use super::multiline_input::mask_literals;
+use super::object_handles::{
+    assign_object_handle, bind_object_handles, referenced_object_handles,
+    substitute_object_handles, unbind_object_handles,
+};
+use super::render_remote_object::runtime_remote_object_to_string;
+use super::repl_state::{DebuggerState, ReplState};
+
+static PROMISE_TIMEOUT: Duration = Duration::from_secs(5);
+static AWAIT_REGEX: OnceLock<Regex> = OnceLock::new();
 
 pub fn evaluate_expression(
     client: &mut CDTClient,
     expression: &str,
     repl_state: ReplState,
 ) -> ReplState {
-    let call_frame_id = repl_state
-        .get_active_call_frame()
-        .map(|call_frame| &call_frame.call_frame_id);
+    let handles = match referenced_object_handles(expression, &repl_state) {
+        Ok(handles) => handles,
+        Err(id) => {
+            println!("Error: no object handle ${}", id);
+            return repl_state.fail();
+        }
+    };
 
-    if call_frame_id.is_none() {
-        return repl_state;
+    // Evaluate on the paused frame, or in the global scope while the program is running.
+    let call_frame_id = match (
+        &repl_state.debugger_state,
+        repl_state.get_active_call_frame(),
+    ) {
+        (DebuggerState::Paused, Some(call_frame)) => Some(call_frame.call_frame_id.to_owned()),
+        (DebuggerState::Running, _) => None,
+        _ => return repl_state.fail(),
+    };
+
+    if let Err(err) = bind_object_handles(client, &handles) {
+        println!("Error while binding object handles: {}", err);
+        return repl_state.fail();
     }
 
-    let remote_object =
-        client.debugger_evaluate_on_call_frame(call_frame_id.unwrap().to_owned(), expression);
+    let expression = &substitute_object_handles(expression);
+
+    let remote_object = match call_frame_id {
+        Some(call_frame_id) => evaluate_on_call_frame(client, call_frame_id, expression),
+        None => evaluate_in_context(client, repl_state.execution_context_id, expression),
+    };
+
+    if let Err(err) = unbind_object_handles(client, &handles) {
+        println!("Error while unbinding object handles: {}", err);
+    }
 
     match remote_object {
-        Ok(obj) => {
+        Ok(obj) if obj.exception_details.is_some() => {
             println!("{}", runtime_remote_object_to_string(obj.result));
+            repl_state.fail()
+        }
+        Ok(obj) => {
+            let (repl_state, handle) = assign_object_handle(&obj.result, repl_state);
+            let value = runtime_remote_object_to_string(obj.result);
+
+            match handle {
+                Some(id) => println!("{} {}", colored(GRAY, &format!("${} =", id)), value),
+                None => println!("{}", value),
+            }
+
+            repl_state
         }
         Err(err) => {
             println!("Error while evaluating: {:?}", err);
+            repl_state.fail()
         }
+    }
+}
+
+/// Evaluate in the global scope of the running program. REPL mode allows top-level `await`,
+/// a promise the expression returns is awaited too.
+fn evaluate_in_context(
+    client: &mut CDTClient,
+    context_id: Option<RuntimeExecutionContextId>,
+    expression: &str,
+) -> CDTClientResult<RuntimeRemoteObject> {
+    client.runtime_evaluate(expression, context_id, PROMISE_TIMEOUT)
+}
+
+/// Evaluate in the paused frame and await a returned promise. `await` is only allowed in async
+/// functions, so an expression using it runs in an async arrow function, which still sees the
+/// variables of the frame.
+fn evaluate_on_call_frame(
+    client: &mut CDTClient,
+    call_frame_id: DebuggerCallFrameId,
+    expression: &str,
+) -> CDTClientResult<RuntimeRemoteObject> {
+    let remote_object = if uses_await(expression) {
+        let wrapped_expression = format!("(async () => {})()", parenthesize(expression));
+        client.debugger_evaluate_on_call_frame(call_frame_id, &wrapped_expression)?
+    } else {
+        client.debugger_evaluate_on_call_frame(call_frame_id, expression)?
     };
 
-    repl_state
+    let promise_object_id = match remote_object.result.subtype.as_deref() {
+        Some("promise") if remote_object.exception_details.is_none() => {
+            remote_object.result.object_id.clone()
+        }
+        _ => None,
+    };
+
+    match promise_object_id {
+        Some(object_id) => client
+            .runtime_await_promise(object_id, PROMISE_TIMEOUT)?
+            .ok_or_else(|| {
+                format!(
+                    "the promise didn't settle within {}s, microtasks don't run while the \
+                     program is paused",
+                    PROMISE_TIMEOUT.as_secs()
+                )
+                .into()
+            }),
+        None => Ok(remote_object),
+    }
+}
+
+/// Make the input parse as an expression, so `{a: 1}` is an object literal instead of a block
+/// with a label. The newline keeps a trailing line comment from commenting out the `)`.
+pub fn parenthesize(expression: &str) -> String {
+    format!("({}\n)", expression)
+}
+
+/// Whether the expression uses `await` outside of strings, regex literals and comments.
+fn uses_await(expression: &str) -> bool {
+    AWAIT_REGEX
+        .get_or_init(|| Regex::new(r"\bawait\b").unwrap())
+        .is_match(&mask_literals(expression))
 }
 
 pub fn evaluate_expression_from_command(
@@ -50,20 +159,21 @@ pub fn evalulate_and_stringify_command(
     evaluate_expression(client, expression, repl_state)
 }
 
-fn runtime_remote_object_to_string(obj: RuntimeRemoteObjectResult) -> String {
-    if obj.value.is_some() {
-        match obj.value.unwrap() {
-            RuntimeRemoteObjectResultValue::String(str) => {
-                format!("\x1b[90m\"\x1b[0m{}\x1b[90m\"\x1b[0m", str)
-            }
-            RuntimeRemoteObjectResultValue::Number(n) => n.to_string(),
-            RuntimeRemoteObjectResultValue::Bool(b) => b.to_string(),
-        }
-    } else if obj.description.is_some() {
-        format!("[\x1b[90mdescription\x1b[0m {}]", obj.description.unwrap())
-    } else if obj.class_name.is_some() {
-        format!("[\x1b[90mclass\x1b[0m {}]", obj.class_name.unwrap())
-    } else {
-        "[\x1b[90m<unknown object>\x1b[0m]".to_string()
+#[cfg(test)]
+mod tests {
+    use super::{parenthesize, uses_await};
+
+    #[test]
+    fn parenthesized_expressions() {
+        assert_eq!(parenthesize("{a: 1}"), "({a: 1}\n)");
+        assert_eq!(parenthesize("1 // one"), "(1 // one\n)");
+    }
+
+    #[test]
+    fn await_outside_literals() {
+        assert!(uses_await("await f()"));
+        assert!(uses_await("1 + (await p)"));
+        assert!(!uses_await("'await'"));
+        assert!(!uses_await("awaited + 1"));
     }
 }
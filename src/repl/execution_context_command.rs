@@ -0,0 +1,74 @@
+use crate::cdt::client::CDTClient;
+use crate::cdt::models::RuntimeExecutionContextId;
+use crate::color::{colored, GRAY};
+
+use super::repl_state::ReplState;
+
+pub fn contexts_command(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    let contexts = client.execution_contexts();
+
+    if contexts.is_empty() {
+        println!("No execution contexts.");
+        return repl_state;
+    }
+
+    let selected_id = repl_state
+        .execution_context_id
+        .or_else(|| contexts.first().map(|context| context.id));
+
+    for context in contexts {
+        let marker = if Some(context.id) == selected_id {
+            ">"
+        } else {
+            " "
+        };
+        let name = if context.name.is_empty() {
+            "(unnamed)"
+        } else {
+            &context.name
+        };
+
+        println!(
+            "{} {:>3} {} {}",
+            marker,
+            context.id,
+            name,
+            colored(GRAY, &context.origin)
+        );
+    }
+
+    repl_state
+}
+
+/// Select the context expressions are evaluated in while the program is running.
+pub fn context_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let id = match line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|id| id.parse::<RuntimeExecutionContextId>().ok())
+    {
+        Some(id) => id,
+        None => {
+            println!("Expected context <id>");
+            return repl_state.fail();
+        }
+    };
+
+    match client
+        .execution_contexts()
+        .iter()
+        .find(|context| context.id == id)
+    {
+        Some(context) => {
+            println!("Evaluating in context {} {}", context.id, context.name);
+            ReplState {
+                execution_context_id: Some(id),
+                ..repl_state
+            }
+        }
+        None => {
+            println!("Error: no execution context {}", id);
+            repl_state.fail()
+        }
+    }
+}
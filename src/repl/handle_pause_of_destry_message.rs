@@ -1,19 +1,52 @@
+use crate::cdt::client::CDTClient;
 use crate::cdt::models::DebuggerPausedResponse;
 
-use super::repl_state::{ReplState, ReplStateCallFrame, DebuggerState};
+use super::display_command::run_display_commands;
+use super::object_handles::release_object_handles;
+use super::pause_reason::print_pause_reason;
+use super::repl_state::{DebuggerState, ReplState, ReplStateCallFrame};
+use super::return_value_command::print_return_value;
+use super::watch_command::print_watches;
 
-pub fn handle_pause_or_destroy_message(message: Option<DebuggerPausedResponse>) -> ReplState {
-    match message {
+pub fn handle_pause_or_destroy_message(
+    client: &mut CDTClient,
+    message: Option<DebuggerPausedResponse>,
+    repl_state: ReplState,
+) -> ReplState {
+    // The program resumed to get here, objects of the previous pause aren't needed anymore.
+    let repl_state = match &message {
+        Some(message) => {
+            print_pause_reason(client, &message.params, &repl_state);
+            release_object_handles(client, repl_state)
+        }
+        None => repl_state,
+    };
+
+    let repl_state = match message {
         Some(message) => ReplState {
             call_frames: Some(ReplStateCallFrame {
                 call_frames: message.params.call_frames.clone(),
                 active_id: 0,
+                async_stack_trace: message.params.async_stack_trace.clone(),
+                async_stack_trace_id: message.params.async_stack_trace_id.clone(),
             }),
             debugger_state: DebuggerState::Paused,
+            continued: false,
+            ..repl_state
         },
         None => ReplState {
             call_frames: None,
             debugger_state: DebuggerState::Exited,
+            ..repl_state
         },
+    };
+
+    if matches!(repl_state.debugger_state, DebuggerState::Paused) {
+        print_return_value(&repl_state);
+        // Watches come first so they stay above a source preview shown by a display command.
+        print_watches(client, &repl_state);
+        run_display_commands(client, repl_state)
+    } else {
+        repl_state
     }
 }
@@ -0,0 +1,79 @@
+use std::fs::File;
+use std::io::{BufWriter, IsTerminal, Write};
+
+use crate::cdt::client::{CDTClient, CDTClientResult};
+use crate::cdt::models::Response;
+
+use super::output_file::output_path;
+use super::repl_state::ReplState;
+
+pub fn heap_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let args = line.split_whitespace().skip(1).collect::<Vec<&str>>();
+
+    match args.as_slice() {
+        ["snapshot"] => heap_snapshot_command(client, None, repl_state),
+        ["snapshot", path] => heap_snapshot_command(client, Some(path), repl_state),
+        _ => {
+            println!("Expected heap snapshot [<file>]");
+            repl_state.fail()
+        }
+    }
+}
+
+fn heap_snapshot_command(
+    client: &mut CDTClient,
+    path: Option<&str>,
+    repl_state: ReplState,
+) -> ReplState {
+    let path = output_path(path, "heap", "heapsnapshot");
+
+    match take_heap_snapshot(client, &path) {
+        Ok(()) => {
+            println!("Heap snapshot saved to {}", path);
+            repl_state
+        }
+        Err(err) => {
+            println!("Error while taking heap snapshot: {}", err);
+            repl_state.fail()
+        }
+    }
+}
+
+/// Take a heap snapshot and write its chunks to the file as they arrive.
+pub fn take_heap_snapshot(client: &mut CDTClient, path: &str) -> CDTClientResult<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    let is_terminal = std::io::stdout().is_terminal();
+
+    client.heap_profiler_take_heap_snapshot(|message| {
+        match message {
+            Response::HeapProfilerAddHeapSnapshotChunk(chunk) => {
+                file.write_all(chunk.params.chunk.as_bytes())?;
+            }
+            // Redrawing the progress line only works in a terminal, redirected output gets
+            // the final line alone.
+            Response::HeapProfilerReportHeapSnapshotProgress(progress) if is_terminal => {
+                let params = &progress.params;
+                print!("\rTaking heap snapshot... {}/{}", params.done, params.total);
+
+                if params.finished {
+                    println!();
+                }
+
+                std::io::stdout().flush()?;
+            }
+            Response::HeapProfilerReportHeapSnapshotProgress(progress) => {
+                let params = &progress.params;
+
+                if params.finished {
+                    println!("Taking heap snapshot... {}/{}", params.done, params.total);
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    })?;
+
+    file.flush()?;
+    Ok(())
+}
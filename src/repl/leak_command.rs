@@ -0,0 +1,111 @@
+use std::rc::Rc;
+
+use crate::cdt::client::{CDTClient, CDTClientResult};
+use crate::cdt::models::DebuggerLocation;
+use crate::heap_snapshot::diff::{print_growth, HeapSnapshotSummary};
+
+use super::heap_command::take_heap_snapshot;
+use super::repl_state::{DebuggerState, ReplState};
+use super::source_location::format_location;
+
+pub fn leak_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    match line.split_whitespace().nth(1) {
+        Some("start") => leak_start_command(client, repl_state),
+        Some("check") => leak_check_command(client, repl_state),
+        _ => {
+            println!("Expected leak start or leak check");
+            repl_state.fail()
+        }
+    }
+}
+
+fn leak_start_command(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    match take_summary(client, &repl_state) {
+        Ok(summary) => {
+            println!("Baseline snapshot taken, run the suspected code and use leak check");
+            ReplState {
+                leak_snapshots: vec![Rc::new(summary)],
+                ..repl_state
+            }
+        }
+        Err(err) => {
+            println!("Error while taking heap snapshot: {}", err);
+            repl_state.fail()
+        }
+    }
+}
+
+/// Compare a new snapshot with all snapshots taken since `leak start`, so objects growing
+/// on every check stand out from one-off allocations.
+fn leak_check_command(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    if repl_state.leak_snapshots.is_empty() {
+        println!("Error: no baseline snapshot, use leak start first");
+        return repl_state.fail();
+    }
+
+    let summary = match take_summary(client, &repl_state) {
+        Ok(summary) => summary,
+        Err(err) => {
+            println!("Error while taking heap snapshot: {}", err);
+            return repl_state.fail();
+        }
+    };
+
+    let mut leak_snapshots = repl_state.leak_snapshots.clone();
+    leak_snapshots.push(Rc::new(summary));
+
+    println!("Growth across {} snapshots", leak_snapshots.len());
+    let summaries = leak_snapshots
+        .iter()
+        .map(|summary| summary.as_ref())
+        .collect::<Vec<_>>();
+
+    print_growth(&summaries, |location| {
+        let location = DebuggerLocation {
+            script_id: location.script_id.to_string(),
+            line_number: location.line_number as u32,
+            column_number: location.column_number as u32,
+        };
+        format_location(client, &location)
+    });
+
+    ReplState {
+        leak_snapshots,
+        ..repl_state
+    }
+}
+
+/// Force GC so only retained objects are counted, then summarize a snapshot saved to a
+/// temporary file.
+fn take_summary(
+    client: &mut CDTClient,
+    repl_state: &ReplState,
+) -> CDTClientResult<HeapSnapshotSummary> {
+    // V8 runs `collectGarbage` as a task which never runs while paused, so it can't be used
+    // here. The snapshot itself is taken after a full GC which does run while paused, but the
+    // locals of the paused frames are still alive and get counted.
+    if matches!(repl_state.debugger_state, DebuggerState::Paused) {
+        println!("Note: the program is paused, objects referenced by its frames are retained");
+    } else {
+        client.heap_profiler_collect_garbage()?;
+    }
+
+    let path = std::env::temp_dir().join(format!(
+        "node-debug-leak-{}.heapsnapshot",
+        std::process::id()
+    ));
+    let path = path.to_string_lossy().to_string();
+
+    // A failed snapshot or summary can leave the file behind too.
+    let summary = match take_heap_snapshot(client, &path) {
+        Ok(()) => HeapSnapshotSummary::from_file(&path),
+        Err(err) => Err(err),
+    };
+    let removed = std::fs::remove_file(&path);
+    let summary = summary?;
+
+    match removed {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(summary),
+    }
+}
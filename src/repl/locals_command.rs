@@ -0,0 +1,67 @@
+use crate::cdt::client::CDTClient;
+use crate::color::{colored, GRAY};
+
+use super::render_remote_object::runtime_remote_object_to_string;
+use super::repl_state::ReplState;
+
+pub fn locals_command(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    let call_frame = repl_state.get_active_call_frame();
+
+    if call_frame.is_none() {
+        println!("Error: no active call frame");
+        return repl_state.fail();
+    }
+
+    let call_frame = call_frame.unwrap();
+
+    if let Some(return_value) = &call_frame.return_value {
+        println!("{}", colored(GRAY, "return"));
+        println!(
+            " <return> = {}",
+            runtime_remote_object_to_string(return_value.clone())
+        );
+    }
+
+    let scopes = call_frame
+        .scope_chain
+        .iter()
+        .filter(|scope| scope.r#type != "global");
+
+    let mut failed = false;
+
+    for scope in scopes {
+        let object_id = match &scope.object.object_id {
+            Some(object_id) => object_id.to_owned(),
+            None => continue,
+        };
+
+        match &scope.name {
+            Some(name) if !name.is_empty() => {
+                println!("{}", colored(GRAY, &format!("{} ({})", scope.r#type, name)))
+            }
+            _ => println!("{}", colored(GRAY, &scope.r#type)),
+        }
+
+        match client.runtime_get_properties(object_id, true) {
+            Ok(properties) => {
+                for property in properties {
+                    let value = property
+                        .value
+                        .map(runtime_remote_object_to_string)
+                        .unwrap_or_else(|| format!("[{}]", colored(GRAY, "accessor")));
+                    println!(" {} = {}", property.name, value);
+                }
+            }
+            Err(err) => {
+                println!("Error while getting variables: {}", err);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        repl_state.fail()
+    } else {
+        repl_state
+    }
+}
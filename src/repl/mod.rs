@@ -1,9 +1,34 @@
+mod apply_command;
+mod async_next_command;
+mod backtrace_command;
+mod blackbox_command;
+mod breakpoint_command;
 mod evaluate_command;
+mod object_handles;
+mod execution_context_command;
+mod restart_frame_command;
+mod return_value_command;
+mod set_variable_command;
 mod show_source_code_command;
 mod continue_to_command;
 mod repl_state;
-mod source_code;
+mod source_command;
+mod source_location;
+mod step_command;
 mod code_preview;
+mod display_command;
+mod leak_command;
+mod locals_command;
 mod handle_pause_of_destry_message;
+mod heap_command;
+mod multiline_input;
+mod output_file;
+mod pause_reason;
+mod profile_command;
+mod coverage_command;
+mod alloc_command;
+mod render_remote_object;
+mod repl_helper;
+mod watch_command;
 
 pub mod start_repl;
@@ -0,0 +1,314 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Keywords after which a `/` starts a regex literal rather than a division.
+static REGEX_PRECEDING_KEYWORDS: &[&str] = &[
+    "return",
+    "typeof",
+    "instanceof",
+    "in",
+    "of",
+    "new",
+    "delete",
+    "void",
+    "throw",
+    "case",
+    "do",
+    "else",
+    "yield",
+    "await",
+];
+
+/// Check whether the input ends inside an unclosed bracket, template literal or block
+/// comment so the REPL should keep reading lines. Unbalanced closing brackets and
+/// unterminated string or regex literals are reported as complete and left for V8 to reject.
+pub fn is_incomplete_input(input: &str) -> bool {
+    let scan = scan(input);
+    !scan.is_invalid && scan.is_incomplete
+}
+
+/// The input with the contents of string, template and regex literals and of comments replaced
+/// by spaces, so it can be searched for code without matching text. Substitutions in template
+/// literals are code, and byte offsets are the same as in the input.
+pub fn mask_literals(input: &str) -> String {
+    scan(input).code
+}
+
+struct Scan {
+    code: String,
+    is_incomplete: bool,
+    is_invalid: bool,
+}
+
+fn scan(input: &str) -> Scan {
+    let mut scanner = Scanner {
+        chars: input.chars().peekable(),
+        code: String::with_capacity(input.len()),
+    };
+    let mut expected_closings: Vec<char> = Vec::new();
+    let mut is_in_block_comment = false;
+    let mut is_invalid = false;
+    // A `/` starts a regex literal where an operand is expected and is a division after one.
+    let mut is_regex_allowed = true;
+
+    while let Some(c) = scanner.chars.next() {
+        if expected_closings.last() == Some(&'`') {
+            match c {
+                '\\' => {
+                    scanner.push_masked(c);
+                    scanner.next_masked();
+                }
+                '`' => {
+                    scanner.push_code(c);
+                    expected_closings.pop();
+                    is_regex_allowed = false;
+                }
+                '$' if scanner.chars.peek() == Some(&'{') => {
+                    scanner.push_code(c);
+                    scanner.next_code();
+                    expected_closings.push('}');
+                    is_regex_allowed = true;
+                }
+                _ => scanner.push_masked(c),
+            }
+
+            continue;
+        }
+
+        match c {
+            '\'' | '"' => {
+                scanner.push_code(c);
+                is_invalid |= !scanner.skip_string_literal(c);
+                is_regex_allowed = false;
+            }
+            '/' if scanner.chars.peek() == Some(&'/') => {
+                scanner.push_masked(c);
+
+                while let Some(c) = scanner.chars.next_if(|c| *c != '\n') {
+                    scanner.push_masked(c);
+                }
+            }
+            '/' if scanner.chars.peek() == Some(&'*') => {
+                scanner.push_masked(c);
+                scanner.next_masked();
+                is_in_block_comment = !scanner.skip_block_comment();
+            }
+            '/' if is_regex_allowed => {
+                scanner.push_code(c);
+                is_invalid |= !scanner.skip_regex_literal();
+                is_regex_allowed = false;
+            }
+            '`' => {
+                scanner.push_code(c);
+                expected_closings.push('`');
+            }
+            '(' | '[' | '{' => {
+                scanner.push_code(c);
+                expected_closings.push(match c {
+                    '(' => ')',
+                    '[' => ']',
+                    _ => '}',
+                });
+                is_regex_allowed = true;
+            }
+            ')' | ']' | '}' => {
+                scanner.push_code(c);
+                is_invalid |= expected_closings.pop() != Some(c);
+                is_regex_allowed = false;
+            }
+            c if is_identifier_char(c) => {
+                scanner.push_code(c);
+                let mut word = c.to_string();
+
+                while let Some(c) = scanner.chars.next_if(|c| is_identifier_char(*c)) {
+                    scanner.push_code(c);
+                    word.push(c);
+                }
+
+                is_regex_allowed = REGEX_PRECEDING_KEYWORDS.contains(&word.as_str());
+            }
+            c if c.is_whitespace() => scanner.push_code(c),
+            _ => {
+                scanner.push_code(c);
+                is_regex_allowed = true;
+            }
+        }
+    }
+
+    Scan {
+        code: scanner.code,
+        is_incomplete: is_in_block_comment || !expected_closings.is_empty(),
+        is_invalid,
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+struct Scanner<'a> {
+    chars: Peekable<Chars<'a>>,
+    code: String,
+}
+
+impl Scanner<'_> {
+    fn push_code(&mut self, c: char) {
+        self.code.push(c);
+    }
+
+    /// Replace the character by spaces of the same length, keeping line breaks.
+    fn push_masked(&mut self, c: char) {
+        match c {
+            '\n' => self.code.push(c),
+            _ => self.code.extend(std::iter::repeat_n(' ', c.len_utf8())),
+        }
+    }
+
+    fn next_code(&mut self) {
+        if let Some(c) = self.chars.next() {
+            self.push_code(c);
+        }
+    }
+
+    fn next_masked(&mut self) {
+        if let Some(c) = self.chars.next() {
+            self.push_masked(c);
+        }
+    }
+
+    /// Skip a string literal after its opening quote, a line break ends an unterminated one.
+    fn skip_string_literal(&mut self, quote: char) -> bool {
+        while let Some(c) = self.chars.next() {
+            match c {
+                '\\' => {
+                    self.push_masked(c);
+                    self.next_masked();
+                }
+                '\n' => {
+                    self.push_code(c);
+                    return false;
+                }
+                c if c == quote => {
+                    self.push_code(c);
+                    return true;
+                }
+                _ => self.push_masked(c),
+            }
+        }
+
+        false
+    }
+
+    fn skip_block_comment(&mut self) -> bool {
+        while let Some(c) = self.chars.next() {
+            self.push_masked(c);
+
+            if c == '*' && self.chars.peek() == Some(&'/') {
+                self.next_masked();
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Skip a regex literal after its opening `/`, a `/` inside a character class doesn't end
+    /// it.
+    fn skip_regex_literal(&mut self) -> bool {
+        let mut is_in_class = false;
+
+        while let Some(c) = self.chars.next() {
+            match c {
+                '\\' => {
+                    self.push_masked(c);
+                    self.next_masked();
+                }
+                '\n' => {
+                    self.push_code(c);
+                    return false;
+                }
+                '/' if !is_in_class => {
+                    self.push_code(c);
+                    return true;
+                }
+                '[' => {
+                    self.push_masked(c);
+                    is_in_class = true;
+                }
+                ']' => {
+                    self.push_masked(c);
+                    is_in_class = false;
+                }
+                _ => self.push_masked(c),
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_incomplete_input, mask_literals};
+
+    #[test]
+    fn brackets() {
+        assert!(is_incomplete_input("function f() {"));
+        assert!(is_incomplete_input("[1, 2,\n3"));
+        assert!(is_incomplete_input("f(\n  { a: 1 }"));
+        assert!(!is_incomplete_input("f({ a: [1] })"));
+        assert!(!is_incomplete_input("f())"));
+    }
+
+    #[test]
+    fn strings() {
+        assert!(!is_incomplete_input("'('"));
+        assert!(!is_incomplete_input("\"{\\\"\""));
+        assert!(is_incomplete_input("f('a)', "));
+        assert!(!is_incomplete_input("'unterminated ("));
+    }
+
+    #[test]
+    fn templates() {
+        assert!(is_incomplete_input("`multi\nline"));
+        assert!(!is_incomplete_input("`{ ${a} (`"));
+        assert!(is_incomplete_input("`${f(`"));
+        assert!(!is_incomplete_input("`${ { a: `}` }.a }`"));
+    }
+
+    #[test]
+    fn comments() {
+        assert!(!is_incomplete_input("1 // ("));
+        assert!(is_incomplete_input("f( // )\n"));
+        assert!(!is_incomplete_input("/* { */ 1"));
+        assert!(is_incomplete_input("/* unterminated"));
+    }
+
+    #[test]
+    fn regex_literals() {
+        assert!(!is_incomplete_input("/\\(/.test(s)"));
+        assert!(!is_incomplete_input("s.match(/[/(]/)"));
+        assert!(!is_incomplete_input("x = /{/g"));
+        assert!(!is_incomplete_input("return /[/]/"));
+        assert!(is_incomplete_input("s.replace(/\\)/g, ("));
+    }
+
+    #[test]
+    fn divisions() {
+        assert!(!is_incomplete_input("a / (b / 2)"));
+        assert!(!is_incomplete_input("f(1) / 2 / (3)"));
+        assert!(is_incomplete_input("x[0] / (1 +"));
+    }
+
+    #[test]
+    fn masked_literals() {
+        assert_eq!(mask_literals("f('a$1', $2)"), "f('   ', $2)");
+        assert_eq!(mask_literals("`a ${$1} b`"), "`  ${$1}  `");
+        assert_eq!(
+            mask_literals("/await/.test(x) // await"),
+            "/     /.test(x)         "
+        );
+        assert_eq!(mask_literals("a /* b\n c */ d"), "a     \n      d");
+        assert_eq!(mask_literals("'é' + 1"), "'  ' + 1");
+    }
+}
@@ -0,0 +1,179 @@
+use std::sync::OnceLock;
+
+use regex::{Captures, Regex};
+use serde_json::json;
+
+use crate::cdt::client::{CDTClient, CDTClientResult};
+use crate::cdt::models::{RuntimeCallArgument, RuntimeRemoteObjectResult};
+
+use super::multiline_input::mask_literals;
+use super::repl_state::{ReplState, ReplStateObjectHandle};
+
+/// Global the referenced objects are bound to while an expression using handles is evaluated,
+/// keyed by a symbol so it can't clash with the globals of the program.
+static HANDLES_GLOBAL: &str = "globalThis[Symbol.for('node-debug.handles')]";
+static HANDLE_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Give the printed object a handle, returns the handle id (`None` for primitive values).
+pub fn assign_object_handle(
+    remote_object: &RuntimeRemoteObjectResult,
+    repl_state: ReplState,
+) -> (ReplState, Option<usize>) {
+    let object_id = match &remote_object.object_id {
+        Some(object_id) => object_id.to_owned(),
+        None => return (repl_state, None),
+    };
+
+    let id = repl_state.object_handle_count + 1;
+    let mut object_handles = repl_state.object_handles.clone();
+    object_handles.push(ReplStateObjectHandle { id, object_id });
+
+    let repl_state = ReplState {
+        object_handles,
+        object_handle_count: id,
+        ..repl_state
+    };
+
+    (repl_state, Some(id))
+}
+
+/// Handles the expression references as `$<id>`, or the id of the first unknown one.
+pub fn referenced_object_handles(
+    expression: &str,
+    repl_state: &ReplState,
+) -> Result<Vec<ReplStateObjectHandle>, usize> {
+    let mut handles: Vec<ReplStateObjectHandle> = Vec::new();
+
+    for captures in handle_regex().captures_iter(&mask_literals(expression)) {
+        let id = captures[2].parse::<usize>().unwrap_or_default();
+
+        if handles.iter().any(|handle| handle.id == id) {
+            continue;
+        }
+
+        match repl_state
+            .object_handles
+            .iter()
+            .find(|handle| handle.id == id)
+        {
+            Some(handle) => handles.push(handle.clone()),
+            None => return Err(id),
+        }
+    }
+
+    Ok(handles)
+}
+
+/// Replace the handles the expression references by the objects bound with
+/// `bind_object_handles`. Literals and comments are masked first, so text like `"$1"` is kept.
+pub fn substitute_object_handles(expression: &str) -> String {
+    let masked_expression = mask_literals(expression);
+    let mut substituted = String::with_capacity(expression.len());
+    let mut last_end = 0;
+
+    for captures in handle_regex().captures_iter(&masked_expression) {
+        let handle = captures.get(0).unwrap();
+        let prefix = &captures[1];
+
+        substituted.push_str(&expression[last_end..handle.start()]);
+        substituted.push_str(prefix);
+        substituted.push_str(&handle_reference(&captures));
+        last_end = handle.end();
+    }
+
+    substituted.push_str(&expression[last_end..]);
+    substituted
+}
+
+/// Bind the objects to a temporary global, so expressions using handles can still be
+/// evaluated on the paused frame and see its variables.
+pub fn bind_object_handles(
+    client: &mut CDTClient,
+    handles: &[ReplStateObjectHandle],
+) -> CDTClientResult<()> {
+    let first_handle = match handles.first() {
+        Some(handle) => handle,
+        None => return Ok(()),
+    };
+
+    let function_declaration = format!(
+        "function (ids, ...objects) {{ {} = Object.fromEntries(ids.map((id, i) => [id, objects[i]])); }}",
+        HANDLES_GLOBAL
+    );
+    let ids = handles.iter().map(|handle| handle.id).collect::<Vec<_>>();
+    let mut arguments = vec![RuntimeCallArgument {
+        value: Some(json!(ids)),
+        unserializable_value: None,
+        object_id: None,
+    }];
+    arguments.extend(handles.iter().map(|handle| RuntimeCallArgument {
+        value: None,
+        unserializable_value: None,
+        object_id: Some(handle.object_id.to_owned()),
+    }));
+
+    client.runtime_call_function_on(
+        first_handle.object_id.to_owned(),
+        &function_declaration,
+        arguments,
+    )?;
+    Ok(())
+}
+
+pub fn unbind_object_handles(
+    client: &mut CDTClient,
+    handles: &[ReplStateObjectHandle],
+) -> CDTClientResult<()> {
+    if let Some(handle) = handles.first() {
+        let function_declaration = format!("function () {{ delete {}; }}", HANDLES_GLOBAL);
+        client.runtime_call_function_on(
+            handle.object_id.to_owned(),
+            &function_declaration,
+            vec![],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// A `$<id>` which isn't part of a longer name or a property access like `a.$1`.
+fn handle_regex() -> &'static Regex {
+    HANDLE_REGEX.get_or_init(|| Regex::new(r"(^|[^\w$.])\$(\d+)\b").unwrap())
+}
+
+fn handle_reference(captures: &Captures) -> String {
+    format!("{}[{}]", HANDLES_GLOBAL, &captures[2])
+}
+
+/// Release the objects returned by evaluations once the program resumed, the handles point
+/// to objects of the previous pause.
+pub fn release_object_handles(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    if let Err(err) = client.runtime_release_object_group() {
+        println!("Error while releasing objects: {}", err);
+    }
+
+    ReplState {
+        object_handles: Vec::new(),
+        ..repl_state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::substitute_object_handles;
+
+    #[test]
+    fn substituted_handles() {
+        let handles = "globalThis[Symbol.for('node-debug.handles')]";
+
+        assert_eq!(
+            substitute_object_handles("$1.a + f($2)"),
+            format!("{0}[1].a + f({0}[2])", handles)
+        );
+        assert_eq!(
+            substitute_object_handles("'$1' + `${$1}$1` // $1"),
+            format!("'$1' + `${{{}[1]}}$1` // $1", handles)
+        );
+        assert_eq!(substitute_object_handles("a.$1 + b$1"), "a.$1 + b$1");
+    }
+}
@@ -0,0 +1,16 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Path given by the user or a `<prefix>-<unix timestamp>.<extension>` file in the working
+/// directory, so consecutive captures don't overwrite each other.
+pub fn output_path(path: Option<&str>, prefix: &str, extension: &str) -> String {
+    match path {
+        Some(path) => path.to_owned(),
+        None => {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+            format!("{}-{}.{}", prefix, timestamp, extension)
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::cdt::client::CDTClient;
+use crate::cdt::models::{DebuggerPausedParams, RuntimeRemoteObjectResult};
+use crate::color::{colored, GRAY};
+
+use super::repl_state::{ReplState, ReplStateBreakpoint};
+use super::source_location::format_location;
+
+/// Print a one-line summary of why the debugger paused and where.
+pub fn print_pause_reason(
+    client: &mut CDTClient,
+    params: &DebuggerPausedParams,
+    repl_state: &ReplState,
+) {
+    let location = match params.call_frames.first() {
+        Some(call_frame) => format_location(client, &call_frame.location),
+        None => return,
+    };
+
+    let header = pause_header(
+        params,
+        &repl_state.breakpoints,
+        repl_state.continued,
+        &location,
+    );
+    println!("{}", colored(GRAY, &header));
+}
+
+/// V8 gives no reason for a `debugger` statement, the end of a step or a requested pause.
+/// Only the first one can stop a program which was continued.
+fn pause_header(
+    params: &DebuggerPausedParams,
+    breakpoints: &[ReplStateBreakpoint],
+    continued: bool,
+    location: &str,
+) -> String {
+    match params.reason.as_str() {
+        "exception" => format!("Paused on {} at {}", exception_to_string(params), location),
+        "promiseRejection" => format!(
+            "Paused on unhandled promise rejection {} at {}",
+            exception_description(params),
+            location
+        ),
+        "OOM" => format!("Paused on out of memory at {}", location),
+        "assert" => format!("Paused on failed assertion at {}", location),
+        _ if !params.hit_breakpoints.is_empty() => {
+            let breakpoint = breakpoints
+                .iter()
+                .find(|breakpoint| params.hit_breakpoints.contains(&breakpoint.breakpoint_id));
+
+            match breakpoint {
+                Some(breakpoint) => {
+                    format!("Paused on breakpoint #{} at {}", breakpoint.id, location)
+                }
+                None => format!("Paused on breakpoint at {}", location),
+            }
+        }
+        "other" if continued => format!("Paused on debugger statement at {}", location),
+        "" | "other" | "ambiguous" | "debugCommand" => format!("Paused at {}", location),
+        reason => format!("Paused on {} at {}", reason, location),
+    }
+}
+
+fn exception_to_string(params: &DebuggerPausedParams) -> String {
+    let is_uncaught = params
+        .data
+        .as_ref()
+        .and_then(|data| data.get("uncaught"))
+        .and_then(|uncaught| uncaught.as_bool())
+        .unwrap_or(false);
+
+    if is_uncaught {
+        format!("uncaught {}", exception_description(params))
+    } else {
+        format!("exception {}", exception_description(params))
+    }
+}
+
+/// The first line of the thrown value's description, e.g. `TypeError: x is not a function`.
+fn exception_description(params: &DebuggerPausedParams) -> String {
+    let exception = params
+        .data
+        .clone()
+        .and_then(|data| serde_json::from_value::<RuntimeRemoteObjectResult>(data).ok());
+
+    exception
+        .and_then(|exception| exception.description)
+        .and_then(|description| description.lines().next().map(str::to_owned))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::cdt::models::DebuggerPausedParams;
+
+    use super::pause_header;
+
+    fn params(reason: &str) -> DebuggerPausedParams {
+        serde_json::from_value(json!({ "callFrames": [], "reason": reason })).unwrap()
+    }
+
+    #[test]
+    fn debugger_statements() {
+        assert_eq!(
+            pause_header(&params("other"), &[], true, "a.js:3"),
+            "Paused on debugger statement at a.js:3"
+        );
+        // The end of a step and a requested pause have no reason either.
+        assert_eq!(
+            pause_header(&params("other"), &[], false, "a.js:3"),
+            "Paused at a.js:3"
+        );
+
+        let mut on_breakpoint = params("other");
+        on_breakpoint.hit_breakpoints = vec!["1:2:0:file:///a.js".to_owned()];
+        assert_eq!(
+            pause_header(&on_breakpoint, &[], true, "a.js:3"),
+            "Paused on breakpoint at a.js:3"
+        );
+    }
+}
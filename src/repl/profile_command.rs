@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use crate::cdt::client::CDTClient;
+use crate::cdt::models::{DebuggerLocation, ProfilerProfile, RuntimeCallFrame};
+use crate::color::{colored, GRAY};
+
+use super::output_file::output_path;
+use super::repl_state::ReplState;
+use super::source_location::format_location;
+
+static TOP_FUNCTIONS: usize = 20;
+
+pub fn profile_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let args = line.split_whitespace().skip(1).collect::<Vec<&str>>();
+
+    match args.as_slice() {
+        ["start"] => profile_start(client, None, repl_state),
+        ["start", "--interval", interval] => match interval.parse::<u32>() {
+            Ok(interval) if interval > 0 => profile_start(client, Some(interval), repl_state),
+            _ => {
+                println!(
+                    "Expected sampling interval in microseconds, got {}",
+                    interval
+                );
+                repl_state.fail()
+            }
+        },
+        ["stop"] => profile_stop(client, None, repl_state),
+        ["stop", path] => profile_stop(client, Some(path), repl_state),
+        _ => {
+            println!("Expected profile start [--interval <us>] or profile stop [<file>]");
+            repl_state.fail()
+        }
+    }
+}
+
+fn profile_start(
+    client: &mut CDTClient,
+    interval: Option<u32>,
+    repl_state: ReplState,
+) -> ReplState {
+    if let Some(interval) = interval {
+        if let Err(err) = client.profiler_set_sampling_interval(interval) {
+            println!("Error while setting the sampling interval: {}", err);
+            return repl_state.fail();
+        }
+    }
+
+    match client.profiler_start() {
+        Ok(()) => {
+            println!("CPU profiling started");
+            repl_state
+        }
+        Err(err) => {
+            println!("Error while starting the profiler: {}", err);
+            repl_state.fail()
+        }
+    }
+}
+
+fn profile_stop(client: &mut CDTClient, path: Option<&str>, repl_state: ReplState) -> ReplState {
+    let profile = match client.profiler_stop() {
+        Ok(profile) => profile,
+        Err(err) => {
+            println!("Error while stopping the profiler: {}", err);
+            return repl_state.fail();
+        }
+    };
+
+    let path = output_path(path, "profile", "cpuprofile");
+
+    let write_result = serde_json::to_string(&profile)
+        .map_err(|err| err.to_string())
+        .and_then(|json| std::fs::write(&path, json).map_err(|err| err.to_string()));
+
+    if let Err(err) = write_result {
+        println!("Error while writing {}: {}", path, err);
+        return repl_state.fail();
+    }
+
+    println!("CPU profile saved to {}", path);
+    print_profile_summary(client, &profile);
+
+    repl_state
+}
+
+#[derive(Default)]
+struct FunctionTime {
+    self_time: f64,
+    total_time: f64,
+}
+
+/// Print the functions with the highest self time. Times are computed from the samples,
+/// so a recursive function is counted once per sample in its total time.
+fn print_profile_summary(client: &mut CDTClient, profile: &ProfilerProfile) {
+    let nodes = profile
+        .nodes
+        .iter()
+        .map(|node| (node.id, node))
+        .collect::<HashMap<_, _>>();
+    let parents = profile
+        .nodes
+        .iter()
+        .flat_map(|node| {
+            node.children
+                .iter()
+                .flatten()
+                .map(move |child| (*child, node.id))
+        })
+        .collect::<HashMap<u32, u32>>();
+
+    let mut functions: HashMap<FunctionKey, FunctionTime> = HashMap::new();
+
+    for (sample, duration) in profile.samples.iter().zip(sample_durations(profile)) {
+        let mut visited = Vec::new();
+        let mut node_id = Some(*sample);
+
+        while let Some(id) = node_id {
+            if let Some(node) = nodes.get(&id) {
+                let key = FunctionKey::from_call_frame(&node.call_frame);
+
+                if id == *sample {
+                    functions.entry(key.clone()).or_default().self_time += duration;
+                }
+
+                if !visited.contains(&key) {
+                    functions.entry(key.clone()).or_default().total_time += duration;
+                    visited.push(key);
+                }
+            }
+
+            node_id = parents.get(&id).copied();
+        }
+    }
+
+    let profile_time = (profile.end_time - profile.start_time).max(1.0);
+    let mut functions = functions
+        .into_iter()
+        .filter(|(key, _)| key.function_name != "(root)")
+        .collect::<Vec<_>>();
+    functions.sort_by(|(_, a), (_, b)| b.self_time.total_cmp(&a.self_time));
+
+    let header = format!(
+        "{:>10} {:>6} {:>10} {:>6}  function",
+        "self ms", "self%", "total ms", "total%"
+    );
+    println!("{}", colored(GRAY, &header));
+
+    for (key, time) in functions.into_iter().take(TOP_FUNCTIONS) {
+        let location = if key.url.is_empty() {
+            String::new()
+        } else {
+            let location = DebuggerLocation {
+                script_id: key.script_id.to_owned(),
+                line_number: key.line_number.max(0) as u32,
+                column_number: key.column_number.max(0) as u32,
+            };
+            let location = format!("({})", format_location(client, &location));
+            format!(" {}", colored(GRAY, &location))
+        };
+
+        println!(
+            "{:>10.1} {:>5.1}% {:>10.1} {:>5.1}%  {}{}",
+            time.self_time / 1000.0,
+            time.self_time / profile_time * 100.0,
+            time.total_time / 1000.0,
+            time.total_time / profile_time * 100.0,
+            key.function_name,
+            location
+        );
+    }
+}
+
+/// Duration of each sample in microseconds, the time until the next sample was taken.
+fn sample_durations(profile: &ProfilerProfile) -> Vec<f64> {
+    let mut timestamps = Vec::with_capacity(profile.time_deltas.len());
+    let mut timestamp = profile.start_time;
+
+    for time_delta in &profile.time_deltas {
+        timestamp += time_delta;
+        timestamps.push(timestamp);
+    }
+
+    timestamps.push(profile.end_time);
+    timestamps
+        .windows(2)
+        .map(|window| (window[1] - window[0]).max(0.0))
+        .collect()
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct FunctionKey {
+    function_name: String,
+    script_id: String,
+    url: String,
+    line_number: i32,
+    column_number: i32,
+}
+
+impl FunctionKey {
+    fn from_call_frame(call_frame: &RuntimeCallFrame) -> FunctionKey {
+        let function_name = if call_frame.function_name.is_empty() {
+            "(anonymous)".to_owned()
+        } else {
+            call_frame.function_name.to_owned()
+        };
+
+        FunctionKey {
+            function_name,
+            script_id: call_frame.script_id.to_owned(),
+            url: call_frame.url.to_owned(),
+            line_number: call_frame.line_number,
+            column_number: call_frame.column_number,
+        }
+    }
+}
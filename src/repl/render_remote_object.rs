@@ -0,0 +1,619 @@
+use crate::cdt::models::{
+    RuntimeObjectPreview, RuntimePropertyPreview, RuntimeRemoteObjectResult,
+    RuntimeRemoteObjectResultValue,
+};
+use crate::color::{colored, BOLD, CYAN, GRAY, GREEN, MAGENTA, RED, YELLOW};
+
+/// Render a remote object the way the Node.js REPL prints values. Objects are rendered
+/// from their `generatePreview` preview, so nesting is limited to what V8 includes in it.
+pub fn runtime_remote_object_to_string(obj: RuntimeRemoteObjectResult) -> String {
+    match obj.r#type.as_str() {
+        "undefined" => colored(GRAY, "undefined"),
+        "string" => match obj.value {
+            Some(RuntimeRemoteObjectResultValue::String(str)) => colored(GREEN, &quote(&str)),
+            _ => obj.description.unwrap_or_default(),
+        },
+        "number" | "bigint" | "boolean" => colored(YELLOW, &primitive_to_string(&obj)),
+        "symbol" => colored(GREEN, &obj.description.unwrap_or_default()),
+        "function" => colored(
+            CYAN,
+            &function_to_string(obj.class_name.as_deref(), obj.description.as_deref()),
+        ),
+        "object" => object_to_string(&obj),
+        _ => obj
+            .description
+            .unwrap_or_else(|| format!("[{}]", colored(GRAY, "<unknown object>"))),
+    }
+}
+
+fn primitive_to_string(obj: &RuntimeRemoteObjectResult) -> String {
+    if let Some(unserializable_value) = &obj.unserializable_value {
+        return unserializable_value.to_owned();
+    }
+
+    if let Some(description) = &obj.description {
+        return description.to_owned();
+    }
+
+    match &obj.value {
+        Some(RuntimeRemoteObjectResultValue::Bool(b)) => b.to_string(),
+        Some(RuntimeRemoteObjectResultValue::Number(n)) => n.to_string(),
+        Some(RuntimeRemoteObjectResultValue::String(str)) => str.to_owned(),
+        Some(RuntimeRemoteObjectResultValue::Json(value)) => value.to_string(),
+        None => String::new(),
+    }
+}
+
+fn object_to_string(obj: &RuntimeRemoteObjectResult) -> String {
+    let description = obj.description.as_deref().unwrap_or("Object");
+
+    match obj.subtype.as_deref() {
+        Some("null") => colored(BOLD, "null"),
+        Some("regexp") => colored(RED, description),
+        Some("date") => colored(MAGENTA, description),
+        Some("error") => description.to_owned(),
+        _ => match &obj.preview {
+            Some(preview) => preview_to_string(preview),
+            None => match &obj.value {
+                Some(RuntimeRemoteObjectResultValue::Json(value)) => value.to_string(),
+                _ => description.to_owned(),
+            },
+        },
+    }
+}
+
+fn preview_to_string(preview: &RuntimeObjectPreview) -> String {
+    let description = preview.description.as_deref().unwrap_or("Object");
+
+    if preview.r#type != "object" {
+        return match preview.r#type.as_str() {
+            "string" => colored(GREEN, &quote(description)),
+            "function" => colored(CYAN, &function_to_string(None, Some(description))),
+            "undefined" => colored(GRAY, "undefined"),
+            "symbol" => colored(GREEN, description),
+            _ => colored(YELLOW, description),
+        };
+    }
+
+    match preview.subtype.as_deref() {
+        Some("null") => colored(BOLD, "null"),
+        Some("regexp") => colored(RED, description),
+        Some("date") => colored(MAGENTA, description),
+        Some("error") => description.lines().next().unwrap_or_default().to_owned(),
+        Some("array") | Some("typedarray") => {
+            // Typed array previews include accessors like `buffer` and `length` too.
+            let is_typed_array = preview.subtype.as_deref() == Some("typedarray");
+            let items = preview
+                .properties
+                .iter()
+                .filter_map(|property| {
+                    if property.name.chars().all(|c| c.is_ascii_digit()) {
+                        Some(property_preview_value_to_string(property))
+                    } else if is_typed_array {
+                        None
+                    } else {
+                        Some(format!(
+                            "{}: {}",
+                            property_name_to_string(&property.name),
+                            property_preview_value_to_string(property)
+                        ))
+                    }
+                })
+                .collect::<Vec<String>>();
+            let prefix = if description.starts_with("Array(") {
+                String::new()
+            } else {
+                format!("{} ", description)
+            };
+
+            format!("{}{}", prefix, wrap("[", &items, preview.overflow, "]"))
+        }
+        Some("map") | Some("set") | Some("weakmap") | Some("weakset") => {
+            let items = preview
+                .entries
+                .iter()
+                .map(|entry| match &entry.key {
+                    Some(key) => format!(
+                        "{} => {}",
+                        preview_to_string(key),
+                        preview_to_string(&entry.value)
+                    ),
+                    None => preview_to_string(&entry.value),
+                })
+                .collect::<Vec<String>>();
+
+            format!(
+                "{} {}",
+                description,
+                wrap("{", &items, preview.overflow, "}")
+            )
+        }
+        Some("promise") => promise_preview_to_string(preview),
+        _ => {
+            let items = preview
+                .properties
+                .iter()
+                .map(|property| {
+                    format!(
+                        "{}: {}",
+                        property_name_to_string(&property.name),
+                        property_preview_value_to_string(property)
+                    )
+                })
+                .collect::<Vec<String>>();
+            // Node shows the target of a proxy, `Proxy(Object)` is described as its target.
+            let description = description
+                .strip_prefix("Proxy(")
+                .and_then(|target| target.strip_suffix(')'))
+                .unwrap_or(description);
+            let prefix = if description == "Object" {
+                String::new()
+            } else {
+                format!("{} ", description)
+            };
+
+            format!("{}{}", prefix, wrap("{", &items, preview.overflow, "}"))
+        }
+    }
+}
+
+fn promise_preview_to_string(preview: &RuntimeObjectPreview) -> String {
+    let get_property = |name: &str| {
+        preview
+            .properties
+            .iter()
+            .find(|property| property.name == name)
+    };
+
+    let state = get_property("[[PromiseState]]").and_then(|property| property.value.as_deref());
+    let result = get_property("[[PromiseResult]]").map(property_preview_value_to_string);
+
+    let content = match (state, result) {
+        (Some("pending"), _) => colored(CYAN, "<pending>"),
+        (Some("rejected"), Some(result)) => format!("{} {}", colored(RED, "<rejected>"), result),
+        (_, Some(result)) => result,
+        (_, None) => colored(CYAN, "<pending>"),
+    };
+
+    format!("Promise {{ {} }}", content)
+}
+
+fn property_preview_value_to_string(property: &RuntimePropertyPreview) -> String {
+    if let Some(value_preview) = &property.value_preview {
+        return preview_to_string(value_preview);
+    }
+
+    let value = property.value.as_deref().unwrap_or_default();
+
+    match (property.r#type.as_str(), property.subtype.as_deref()) {
+        ("string", _) => colored(GREEN, &quote(value)),
+        ("function", _) => colored(CYAN, "[Function]"),
+        ("undefined", _) => colored(GRAY, "undefined"),
+        ("symbol", _) => colored(GREEN, value),
+        ("object", Some("null")) => colored(BOLD, "null"),
+        ("object", Some("regexp")) => colored(RED, value),
+        ("object", Some("date")) => colored(MAGENTA, value),
+        ("object", _) => format!("[{}]", value),
+        _ => colored(YELLOW, value),
+    }
+}
+
+/// Render a function from its description, which V8 sets to the function source. The class
+/// name is the kind of function, like `AsyncFunction`, previews only have the source to tell.
+fn function_to_string(class_name: Option<&str>, description: Option<&str>) -> String {
+    let description = description.unwrap_or_default().trim_start();
+
+    if let Some(class_source) = description.strip_prefix("class ") {
+        let name = identifier_prefix(class_source);
+        return if name.is_empty() {
+            "[class (anonymous)]".to_string()
+        } else {
+            format!("[class {}]", name)
+        };
+    }
+
+    let signature = description
+        .trim_start_matches("async ")
+        .trim_start_matches("function")
+        .trim_start_matches('*')
+        .trim_start();
+    let name = if description.contains("function") || !description.contains("=>") {
+        identifier_prefix(signature)
+    } else {
+        ""
+    };
+
+    let kind = class_name.unwrap_or_else(|| function_kind(description));
+
+    if name.is_empty() {
+        format!("[{} (anonymous)]", kind)
+    } else {
+        format!("[{}: {}]", kind, name)
+    }
+}
+
+fn function_kind(description: &str) -> &'static str {
+    let (is_async, rest) = match description.strip_prefix("async") {
+        Some(rest) if rest.starts_with(|c: char| c.is_whitespace() || c == '(') => {
+            (true, rest.trim_start())
+        }
+        _ => (false, description),
+    };
+    let is_generator = rest
+        .strip_prefix("function")
+        .unwrap_or(rest)
+        .trim_start()
+        .starts_with('*');
+
+    match (is_async, is_generator) {
+        (true, true) => "AsyncGeneratorFunction",
+        (true, false) => "AsyncFunction",
+        (false, true) => "GeneratorFunction",
+        (false, false) => "Function",
+    }
+}
+
+fn identifier_prefix(input: &str) -> &str {
+    let end = input
+        .char_indices()
+        .find(|(_, c)| !(c.is_alphanumeric() || *c == '_' || *c == '$'))
+        .map(|(i, _)| i)
+        .unwrap_or(input.len());
+
+    &input[..end]
+}
+
+fn property_name_to_string(name: &str) -> String {
+    let is_identifier = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+
+    if is_identifier {
+        name.to_owned()
+    } else {
+        colored(GREEN, &quote(name))
+    }
+}
+
+/// Quote a string like `util.inspect`: single quotes unless the string contains them, then
+/// double quotes or backticks, and control characters escaped.
+fn quote(str: &str) -> String {
+    let quote = if !str.contains('\'') {
+        '\''
+    } else if !str.contains('"') {
+        '"'
+    } else if !str.contains('`') && !str.contains("${") {
+        '`'
+    } else {
+        '\''
+    };
+
+    let mut quoted = String::with_capacity(str.len() + 2);
+    quoted.push(quote);
+
+    for c in str.chars() {
+        match c {
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            '\u{8}' => quoted.push_str("\\b"),
+            '\u{c}' => quoted.push_str("\\f"),
+            '\\' => quoted.push_str("\\\\"),
+            c if c == quote => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c if c.is_ascii_control() => quoted.push_str(&format!("\\x{:02X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push(quote);
+    quoted
+}
+
+fn wrap(open: &str, items: &[String], overflow: bool, close: &str) -> String {
+    let mut items = items.to_vec();
+
+    if overflow {
+        items.push("...".to_string());
+    }
+
+    if items.is_empty() {
+        format!("{}{}", open, close)
+    } else {
+        format!("{} {} {}", open, items.join(", "), close)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+    use serde_json::{json, Value};
+
+    use super::runtime_remote_object_to_string;
+
+    /// Render a remote object as V8 sends it, without the color escape codes.
+    fn render(remote_object: Value) -> String {
+        let rendered =
+            runtime_remote_object_to_string(serde_json::from_value(remote_object).unwrap());
+        Regex::new("\x1b\\[[0-9;]*m")
+            .unwrap()
+            .replace_all(&rendered, "")
+            .into_owned()
+    }
+
+    #[test]
+    fn primitives() {
+        assert_eq!(
+            render(json!({"type": "object", "subtype": "null", "value": null})),
+            "null"
+        );
+        assert_eq!(render(json!({"type": "undefined"})), "undefined");
+        assert_eq!(
+            render(json!({"type": "number", "unserializableValue": "NaN", "description": "NaN"})),
+            "NaN"
+        );
+        assert_eq!(
+            render(json!({"type": "number", "unserializableValue": "-0", "description": "-0"})),
+            "-0"
+        );
+        assert_eq!(
+            render(json!({"type": "bigint", "unserializableValue": "10n", "description": "10n"})),
+            "10n"
+        );
+        assert_eq!(
+            render(json!({"type": "symbol", "description": "Symbol(s)"})),
+            "Symbol(s)"
+        );
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(render(json!({"type": "string", "value": "str"})), "'str'");
+        assert_eq!(
+            render(json!({
+                "type": "object",
+                "className": "Object",
+                "description": "Object",
+                "preview": {
+                    "type": "object",
+                    "description": "Object",
+                    "overflow": false,
+                    "properties": [{"name": "s", "type": "string", "value": "str"}]
+                }
+            })),
+            "{ s: 'str' }"
+        );
+    }
+
+    #[test]
+    fn quotes_and_escapes_in_strings() {
+        let string = |value: &str| render(json!({"type": "string", "value": value}));
+
+        assert_eq!(string("it's"), r#""it's""#);
+        assert_eq!(string(r#"it's "quoted""#), r#"`it's "quoted"`"#);
+        assert_eq!(string(r#"it's "${quoted}"`"#), r#"'it\'s "${quoted}"`'"#);
+        assert_eq!(string(r"C:\dir"), r"'C:\\dir'");
+        assert_eq!(string("a\nb\tc"), r"'a\nb\tc'");
+        assert_eq!(string("\u{7}"), r"'\x07'");
+        assert_eq!(
+            render(json!({
+                "type": "object",
+                "className": "Object",
+                "description": "Object",
+                "preview": {
+                    "type": "object",
+                    "description": "Object",
+                    "overflow": false,
+                    "properties": [{"name": "it's", "type": "string", "value": "a\nb"}]
+                }
+            })),
+            r#"{ "it's": 'a\nb' }"#
+        );
+    }
+
+    #[test]
+    fn functions() {
+        let function = |class_name: &str, description: &str| {
+            render(json!({"type": "function", "className": class_name, "description": description}))
+        };
+
+        assert_eq!(function("Function", "function f(a) {}"), "[Function: f]");
+        assert_eq!(
+            function("AsyncFunction", "async function f() {}"),
+            "[AsyncFunction: f]"
+        );
+        assert_eq!(
+            function("GeneratorFunction", "function* g() {}"),
+            "[GeneratorFunction: g]"
+        );
+        assert_eq!(
+            function("AsyncFunction", "async () => 1"),
+            "[AsyncFunction (anonymous)]"
+        );
+        assert_eq!(function("Function", "class A {}"), "[class A]");
+        assert_eq!(
+            render(json!({
+                "type": "object",
+                "className": "Object",
+                "description": "Object",
+                "preview": {
+                    "type": "object",
+                    "description": "Object",
+                    "overflow": false,
+                    "properties": [{
+                        "name": "g",
+                        "type": "function",
+                        "valuePreview": {
+                            "type": "function",
+                            "description": "async function* g() {}",
+                            "overflow": false,
+                            "properties": []
+                        }
+                    }]
+                }
+            })),
+            "{ g: [AsyncGeneratorFunction: g] }"
+        );
+    }
+
+    #[test]
+    fn objects_without_preview() {
+        assert_eq!(
+            render(json!({
+                "type": "object",
+                "subtype": "promise",
+                "className": "Promise",
+                "description": "Promise",
+                "objectId": "1"
+            })),
+            "Promise"
+        );
+        assert_eq!(
+            render(json!({
+                "type": "object",
+                "subtype": "array",
+                "className": "Array",
+                "description": "Array(3)",
+                "objectId": "2"
+            })),
+            "Array(3)"
+        );
+    }
+
+    #[test]
+    fn collections() {
+        assert_eq!(
+            render(json!({
+                "type": "object",
+                "subtype": "map",
+                "className": "Map",
+                "description": "Map(1)",
+                "preview": {
+                    "type": "object",
+                    "subtype": "map",
+                    "description": "Map(1)",
+                    "overflow": false,
+                    "properties": [{"name": "size", "type": "number", "value": "1"}],
+                    "entries": [{
+                        "key": {"type": "string", "description": "a", "overflow": false, "properties": []},
+                        "value": {"type": "number", "description": "1", "overflow": false, "properties": []}
+                    }]
+                }
+            })),
+            "Map(1) { 'a' => 1 }"
+        );
+        assert_eq!(
+            render(json!({
+                "type": "object",
+                "subtype": "set",
+                "className": "Set",
+                "description": "Set(2)",
+                "preview": {
+                    "type": "object",
+                    "subtype": "set",
+                    "description": "Set(2)",
+                    "overflow": false,
+                    "properties": [{"name": "size", "type": "number", "value": "2"}],
+                    "entries": [
+                        {"value": {"type": "number", "description": "1", "overflow": false, "properties": []}},
+                        {"value": {"type": "string", "description": "b", "overflow": false, "properties": []}}
+                    ]
+                }
+            })),
+            "Set(2) { 1, 'b' }"
+        );
+        assert_eq!(
+            render(json!({
+                "type": "object",
+                "subtype": "typedarray",
+                "className": "Uint8Array",
+                "description": "Uint8Array(2)",
+                "preview": {
+                    "type": "object",
+                    "subtype": "typedarray",
+                    "description": "Uint8Array(2)",
+                    "overflow": false,
+                    "properties": [
+                        {"name": "0", "type": "number", "value": "1"},
+                        {"name": "1", "type": "number", "value": "2"},
+                        {"name": "buffer", "type": "object", "value": "ArrayBuffer(2)", "subtype": "arraybuffer"},
+                        {"name": "byteLength", "type": "number", "value": "2"},
+                        {"name": "byteOffset", "type": "number", "value": "0"},
+                        {"name": "length", "type": "number", "value": "2"},
+                        {"name": "Symbol(Symbol.toStringTag)", "type": "string", "value": "Uint8Array"}
+                    ]
+                }
+            })),
+            "Uint8Array(2) [ 1, 2 ]"
+        );
+    }
+
+    #[test]
+    fn promises() {
+        assert_eq!(
+            render(json!({
+                "type": "object",
+                "subtype": "promise",
+                "className": "Promise",
+                "description": "Promise",
+                "preview": {
+                    "type": "object",
+                    "subtype": "promise",
+                    "description": "Promise",
+                    "overflow": false,
+                    "properties": [
+                        {"name": "[[PromiseState]]", "type": "string", "value": "fulfilled"},
+                        {"name": "[[PromiseResult]]", "type": "number", "value": "5"}
+                    ]
+                }
+            })),
+            "Promise { 5 }"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let stack = "Error: boom\n    at <anonymous>:1:1";
+
+        assert_eq!(
+            render(json!({
+                "type": "object",
+                "subtype": "error",
+                "className": "Error",
+                "description": stack,
+                "preview": {
+                    "type": "object",
+                    "subtype": "error",
+                    "description": stack,
+                    "overflow": false,
+                    "properties": [{"name": "message", "type": "string", "value": "boom"}]
+                }
+            })),
+            stack
+        );
+    }
+
+    #[test]
+    fn proxies() {
+        assert_eq!(
+            render(json!({
+                "type": "object",
+                "subtype": "proxy",
+                "className": "Object",
+                "description": "Proxy(Object)",
+                "preview": {
+                    "type": "object",
+                    "subtype": "proxy",
+                    "description": "Proxy(Object)",
+                    "overflow": false,
+                    "properties": [{"name": "a", "type": "number", "value": "1"}]
+                }
+            })),
+            "{ a: 1 }"
+        );
+    }
+}
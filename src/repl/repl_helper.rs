@@ -0,0 +1,260 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+
+use crate::cdt::client::CDTClient;
+
+use super::repl_state::ReplState;
+
+static COMMANDS: &[&str] = &[
+    "s",
+    "show",
+    "sm",
+    "show-minified",
+    "c",
+    "continue",
+    "i",
+    "step-into",
+    "o",
+    "step-out",
+    "n",
+    "next",
+    "ns",
+    "u",
+    "until",
+    "finish",
+    "return",
+    "an",
+    "async-next",
+    "l",
+    "locals",
+    "set",
+    "bt",
+    "backtrace",
+    "restart-frame",
+    "apply",
+    "hotfix",
+    "display",
+    "undisplay",
+    "source",
+    "ct",
+    "b",
+    "break",
+    "break-fn",
+    "bl",
+    "breakpoints",
+    "watch",
+    "unwatch",
+    "watches",
+    "blackbox",
+    "unblackbox",
+    "profile",
+    "coverage",
+    "heap",
+    "leak",
+    "alloc",
+    "resume",
+    "pause",
+    "contexts",
+    "context",
+    "e",
+    "es",
+    "q",
+    "quit",
+    "h",
+    "help",
+];
+
+static BREAKPOINT_COMMANDS: &[&str] = &["b", "break"];
+
+pub struct ReplHelper {
+    client: Rc<RefCell<CDTClient>>,
+    repl_state: ReplState,
+}
+
+impl ReplHelper {
+    pub fn new(client: Rc<RefCell<CDTClient>>, repl_state: ReplState) -> ReplHelper {
+        ReplHelper { client, repl_state }
+    }
+
+    pub fn set_repl_state(&mut self, repl_state: ReplState) {
+        self.repl_state = repl_state;
+    }
+
+    fn complete_command(&self, word: &str) -> Vec<String> {
+        COMMANDS
+            .iter()
+            .filter(|command| command.starts_with(word))
+            .map(|command| command.to_string())
+            .collect()
+    }
+
+    fn complete_script_url(&self, word: &str) -> Vec<String> {
+        let client = match self.client.try_borrow() {
+            Ok(client) => client,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut urls = client
+            .scripts()
+            .values()
+            .map(|script| script.url.to_owned())
+            .filter(|url| !url.is_empty() && url.contains(word))
+            .collect::<Vec<String>>();
+
+        urls.sort();
+        urls.dedup();
+        urls
+    }
+
+    /// Complete the JS identifier or property access ending at the end of `line`. Returns
+    /// the position where the completed word starts together with the candidates.
+    fn complete_expression(&self, line: &str) -> (usize, Vec<String>) {
+        let token_start = line
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_identifier_char(*c) || *c == '.')
+            .last()
+            .map(|(i, _)| i)
+            .unwrap_or(line.len());
+        let token = &line[token_start..];
+
+        match token.rsplit_once('.') {
+            Some((object_expression, property)) => {
+                let start = line.len() - property.len();
+                let names = self.get_property_names(object_expression);
+                (start, filter_candidates(names, property))
+            }
+            None => {
+                let names = self.get_variable_names();
+                (token_start, filter_candidates(names, token))
+            }
+        }
+    }
+
+    fn get_property_names(&self, object_expression: &str) -> Vec<String> {
+        let call_frame_id = match self.repl_state.get_active_call_frame() {
+            Some(call_frame) => call_frame.call_frame_id.to_owned(),
+            None => return Vec::new(),
+        };
+
+        if object_expression.is_empty() {
+            return Vec::new();
+        }
+
+        let mut client = match self.client.try_borrow_mut() {
+            Ok(client) => client,
+            Err(_) => return Vec::new(),
+        };
+
+        let object_id = client
+            .debugger_evaluate_on_call_frame_without_side_effects(call_frame_id, object_expression)
+            .ok()
+            .and_then(|remote_object| remote_object.result.object_id);
+
+        match object_id {
+            Some(object_id) => client
+                .runtime_get_properties(object_id, false)
+                .map(|properties| properties.into_iter().map(|p| p.name).collect())
+                .unwrap_or_default(),
+            None => Vec::new(),
+        }
+    }
+
+    fn get_variable_names(&self) -> Vec<String> {
+        let mut client = match self.client.try_borrow_mut() {
+            Ok(client) => client,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut names = client
+            .runtime_global_lexical_scope_names()
+            .unwrap_or_default();
+
+        let scope_object_ids = self
+            .repl_state
+            .get_active_call_frame()
+            .map(|call_frame| {
+                call_frame
+                    .scope_chain
+                    .iter()
+                    .filter_map(|scope| scope.object.object_id.to_owned())
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_default();
+
+        for object_id in scope_object_ids {
+            if let Ok(properties) = client.runtime_get_properties(object_id, false) {
+                names.extend(properties.into_iter().map(|property| property.name));
+            }
+        }
+
+        names
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+
+        match line.split_once(' ') {
+            Some((command, _)) if BREAKPOINT_COMMANDS.contains(&command) => {
+                let start = line.rfind(' ').map(|i| i + 1).unwrap_or(0);
+                Ok((start, self.complete_script_url(&line[start..])))
+            }
+            Some(_) => Ok(self.complete_expression(line)),
+            None => {
+                let (start, mut candidates) = self.complete_expression(line);
+
+                if start == 0 {
+                    let mut commands = self.complete_command(line);
+                    commands.append(&mut candidates);
+                    candidates = commands;
+                }
+
+                Ok((start, candidates))
+            }
+        }
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+/// Required by `Helper`, but accepts every line. Incomplete input is continued by `read_input`,
+/// a validator couldn't show the `..` prompt for the following lines.
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+fn filter_candidates(names: Vec<String>, prefix: &str) -> Vec<String> {
+    let mut candidates = names
+        .into_iter()
+        .filter(|name| name.starts_with(prefix))
+        .filter(|name| name.chars().next().is_some_and(|c| !c.is_ascii_digit()))
+        .filter(|name| name.chars().all(is_identifier_char))
+        .collect::<Vec<String>>();
+
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
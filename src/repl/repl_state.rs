@@ -1,22 +1,77 @@
-use crate::cdt::models::DebuggerPausedCallFrame;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use regex::Regex;
+
+use crate::cdt::models::{
+    DebuggerPausedCallFrame, RuntimeExecutionContextId, RuntimeRemoteObjectId, RuntimeStackTrace,
+    RuntimeStackTraceId,
+};
+use crate::heap_snapshot::diff::HeapSnapshotSummary;
 
 #[derive(Clone)]
 pub struct ReplState {
     pub call_frames: Option<ReplStateCallFrame>,
     pub debugger_state: DebuggerState,
+    pub breakpoints: Vec<ReplStateBreakpoint>,
+    pub watches: Vec<String>,
+    pub display: Vec<String>,
+    pub blackbox_patterns: Vec<Regex>,
+    /// Context expressions are evaluated in while the program is running, the main context
+    /// when not selected.
+    pub execution_context_id: Option<RuntimeExecutionContextId>,
+    /// Objects printed since the program last resumed, referenced as `$<id>` in expressions.
+    pub object_handles: Vec<ReplStateObjectHandle>,
+    /// Number of handles assigned so far, handles aren't reused after they're released.
+    pub object_handle_count: usize,
+    /// Summaries of the snapshots taken since `leak start`.
+    pub leak_snapshots: Vec<Rc<HeapSnapshotSummary>>,
+    /// Command files being run, the innermost last, so a file can't source itself.
+    pub sourced_files: Vec<PathBuf>,
+    pub command_failed: bool,
+    /// Whether the program runs until it pauses by itself, not until a step ends or a pause
+    /// is requested. A pause without a reason comes from a `debugger` statement then.
+    pub continued: bool,
 }
 
 impl ReplState {
+    pub fn new() -> ReplState {
+        ReplState {
+            call_frames: None,
+            debugger_state: DebuggerState::Exited,
+            breakpoints: Vec::new(),
+            watches: Vec::new(),
+            display: Vec::new(),
+            blackbox_patterns: Vec::new(),
+            execution_context_id: None,
+            object_handles: Vec::new(),
+            object_handle_count: 0,
+            leak_snapshots: Vec::new(),
+            sourced_files: Vec::new(),
+            command_failed: false,
+            continued: false,
+        }
+    }
+
+    /// Mark the last command as failed, batch sessions stop on the first failure.
+    pub fn fail(self) -> ReplState {
+        ReplState {
+            command_failed: true,
+            ..self
+        }
+    }
+
     pub fn get_active_call_frame(&self) -> Option<&DebuggerPausedCallFrame> {
         self.call_frames
             .as_ref()
-            .and_then(|call_frames| Some(&call_frames.call_frames[call_frames.active_id]))
+            .and_then(|call_frames| call_frames.call_frames.get(call_frames.active_id))
     }
 }
 
 #[derive(Clone)]
 pub enum DebuggerState {
     Paused,
+    Running,
     Exited,
 }
 
@@ -24,4 +79,22 @@ pub enum DebuggerState {
 pub struct ReplStateCallFrame {
     pub call_frames: Vec<DebuggerPausedCallFrame>,
     pub active_id: usize,
+    pub async_stack_trace: Option<RuntimeStackTrace>,
+    pub async_stack_trace_id: Option<RuntimeStackTraceId>,
+}
+
+#[derive(Clone)]
+pub struct ReplStateBreakpoint {
+    pub id: usize,
+    pub breakpoint_id: String,
+    pub url: String,
+    pub line_number: u32,
+    /// Expression of the function for breakpoints set with `break-fn`.
+    pub function: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct ReplStateObjectHandle {
+    pub id: usize,
+    pub object_id: RuntimeRemoteObjectId,
 }
@@ -0,0 +1,41 @@
+use crate::cdt::client::CDTClient;
+
+use super::handle_pause_of_destry_message::handle_pause_or_destroy_message;
+use super::repl_state::ReplState;
+
+pub fn restart_frame_command(
+    client: &mut CDTClient,
+    line: &str,
+    repl_state: ReplState,
+) -> ReplState {
+    let call_frames = match &repl_state.call_frames {
+        Some(call_frames) => call_frames,
+        None => {
+            println!("Error: no active call frame");
+            return repl_state.fail();
+        }
+    };
+
+    let number_string = line.split_once(' ').map(|(_, n)| n.trim()).unwrap_or("");
+    let number = if number_string.is_empty() {
+        Some(call_frames.active_id)
+    } else {
+        number_string.parse::<usize>().ok()
+    };
+
+    let call_frame = match number.and_then(|number| call_frames.call_frames.get(number)) {
+        Some(call_frame) => call_frame,
+        None => {
+            println!("Expected call frame number, got {}", number_string);
+            return repl_state.fail();
+        }
+    };
+
+    match client.debugger_restart_frame(call_frame.call_frame_id.to_owned()) {
+        Ok(message) => handle_pause_or_destroy_message(client, message, repl_state),
+        Err(err) => {
+            println!("Error while restarting frame: {}", err);
+            repl_state.fail()
+        }
+    }
+}
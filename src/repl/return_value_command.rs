@@ -0,0 +1,101 @@
+use crate::cdt::client::CDTClient;
+use crate::cdt::models::{DebuggerPausedCallFrame, RuntimeCallArgument};
+use crate::color::{colored, GRAY};
+
+use super::evaluate_command::parenthesize;
+use super::render_remote_object::runtime_remote_object_to_string;
+use super::repl_state::ReplState;
+
+/// Replace the value the top call frame is about to return. V8 only allows it while paused
+/// at the return position, which is when the frame carries `returnValue`.
+pub fn return_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let expression = line
+        .split_once(' ')
+        .map(|(_, expression)| expression.trim())
+        .unwrap_or("");
+
+    if expression.is_empty() {
+        println!("Expected expression to return");
+        return repl_state.fail();
+    }
+
+    let call_frame = match get_top_call_frame(&repl_state) {
+        Some(call_frame) if call_frame.return_value.is_some() => call_frame,
+        Some(_) => {
+            println!("Error: the function is not paused at a return");
+            return repl_state.fail();
+        }
+        None => {
+            println!("Error: no active call frame");
+            return repl_state.fail();
+        }
+    };
+
+    let remote_object = match client.debugger_evaluate_on_call_frame(
+        call_frame.call_frame_id.to_owned(),
+        &parenthesize(expression),
+    ) {
+        Ok(remote_object) if remote_object.exception_details.is_none() => remote_object,
+        Ok(remote_object) => {
+            println!(
+                "Error while evaluating: {}",
+                runtime_remote_object_to_string(remote_object.result)
+            );
+            return repl_state.fail();
+        }
+        Err(err) => {
+            println!("Error while evaluating: {}", err);
+            return repl_state.fail();
+        }
+    };
+
+    let new_value = match RuntimeCallArgument::from_remote_object(&remote_object.result) {
+        Ok(new_value) => new_value,
+        Err(err) => {
+            println!("Error: {}", err);
+            return repl_state.fail();
+        }
+    };
+
+    if let Err(err) = client.debugger_set_return_value(new_value) {
+        println!("Error while setting the return value: {}", err);
+        return repl_state.fail();
+    }
+
+    let mut repl_state = repl_state;
+
+    if let Some(call_frame) = repl_state
+        .call_frames
+        .as_mut()
+        .and_then(|call_frames| call_frames.call_frames.first_mut())
+    {
+        call_frame.return_value = Some(remote_object.result.clone());
+    }
+
+    println!(
+        "<return> = {}",
+        runtime_remote_object_to_string(remote_object.result)
+    );
+    repl_state
+}
+
+/// Print the value the top call frame returns if the debugger is paused at its return.
+pub fn print_return_value(repl_state: &ReplState) {
+    let return_value =
+        get_top_call_frame(repl_state).and_then(|call_frame| call_frame.return_value.as_ref());
+
+    if let Some(return_value) = return_value {
+        println!(
+            "{} = {}",
+            colored(GRAY, "<return>"),
+            runtime_remote_object_to_string(return_value.clone())
+        );
+    }
+}
+
+fn get_top_call_frame(repl_state: &ReplState) -> Option<&DebuggerPausedCallFrame> {
+    repl_state
+        .call_frames
+        .as_ref()
+        .and_then(|call_frames| call_frames.call_frames.first())
+}
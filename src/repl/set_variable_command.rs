@@ -0,0 +1,147 @@
+use serde_json::json;
+
+use crate::cdt::client::{CDTClient, CDTClientResult};
+use crate::cdt::models::{DebuggerScope, RuntimeCallArgument};
+use crate::color::{colored, GRAY};
+
+use super::evaluate_command::parenthesize;
+use super::render_remote_object::runtime_remote_object_to_string;
+use super::repl_state::ReplState;
+
+pub fn set_variable_command(
+    client: &mut CDTClient,
+    line: &str,
+    repl_state: ReplState,
+) -> ReplState {
+    let assignment = line
+        .split_once(' ')
+        .and_then(|(_, assignment)| assignment.split_once('='))
+        .map(|(name, expression)| (name.trim(), expression.trim()))
+        .filter(|(name, expression)| is_identifier(name) && !expression.is_empty());
+
+    if assignment.is_none() {
+        println!("Expected <name> = <expression>");
+        return repl_state.fail();
+    }
+
+    let (name, expression) = assignment.unwrap();
+
+    let call_frame = match repl_state.get_active_call_frame() {
+        Some(call_frame) => call_frame,
+        None => {
+            println!("Error: no active call frame");
+            return repl_state.fail();
+        }
+    };
+
+    let remote_object = match client.debugger_evaluate_on_call_frame(
+        call_frame.call_frame_id.to_owned(),
+        &parenthesize(expression),
+    ) {
+        Ok(remote_object) if remote_object.exception_details.is_none() => remote_object,
+        Ok(remote_object) => {
+            println!(
+                "Error while evaluating: {}",
+                runtime_remote_object_to_string(remote_object.result)
+            );
+            return repl_state.fail();
+        }
+        Err(err) => {
+            println!("Error while evaluating: {}", err);
+            return repl_state.fail();
+        }
+    };
+
+    let new_value = match RuntimeCallArgument::from_remote_object(&remote_object.result) {
+        Ok(new_value) => new_value,
+        Err(err) => {
+            println!("Error: {}", err);
+            return repl_state.fail();
+        }
+    };
+
+    // The scope chain goes from the innermost scope, so the first scope declaring the name
+    // is the one the name resolves to in the paused frame.
+    let scope = call_frame
+        .scope_chain
+        .iter()
+        .enumerate()
+        .find(|(_, scope)| {
+            scope.object.object_id.as_ref().is_some_and(|object_id| {
+                client
+                    .runtime_get_properties(object_id.to_owned(), true)
+                    .map(|properties| properties.iter().any(|property| property.name == name))
+                    .unwrap_or(false)
+            })
+        });
+
+    let (scope_number, scope) = match scope {
+        Some(scope) => scope,
+        None => {
+            println!(
+                "Error: {} is not declared in any scope of the active frame",
+                name
+            );
+            return repl_state.fail();
+        }
+    };
+
+    let result = client
+        .debugger_set_variable_value(
+            scope_number as i32,
+            name,
+            new_value.clone(),
+            call_frame.call_frame_id.to_owned(),
+        )
+        .and_then(|()| update_scope_object(client, scope, name, new_value));
+
+    match result {
+        Ok(()) => {
+            println!(
+                "{} = {} {}",
+                name,
+                runtime_remote_object_to_string(remote_object.result),
+                colored(GRAY, &format!("({} scope)", scope.r#type))
+            );
+            repl_state
+        }
+        Err(err) => {
+            println!("Error while setting {}: {}", name, err);
+            repl_state.fail()
+        }
+    }
+}
+
+/// The scope objects are copies taken when the program paused, `l` reads them, so the new
+/// value is written to the copy too.
+fn update_scope_object(
+    client: &mut CDTClient,
+    scope: &DebuggerScope,
+    name: &str,
+    new_value: RuntimeCallArgument,
+) -> CDTClientResult<()> {
+    let object_id = match &scope.object.object_id {
+        Some(object_id) => object_id.to_owned(),
+        None => return Ok(()),
+    };
+
+    let name = RuntimeCallArgument {
+        value: Some(json!(name)),
+        ..RuntimeCallArgument::default()
+    };
+
+    client.runtime_call_function_on(
+        object_id,
+        "function (name, value) { this[name] = value; }",
+        vec![name, new_value],
+    )?;
+    Ok(())
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
@@ -0,0 +1,115 @@
+use crate::cdt::client::CDTClient;
+
+use super::multiline_input::is_incomplete_input;
+use super::repl_state::{DebuggerState, ReplState};
+use super::start_repl::run_command;
+
+pub fn source_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let path = line
+        .split_once(' ')
+        .map(|(_, path)| path.trim())
+        .unwrap_or("");
+    run_commands_file(client, path, repl_state)
+}
+
+/// Run REPL commands from a file and stop on the first failing one. Every command is
+/// echoed with the REPL prompt so the output of a session is stable and diffable.
+pub fn run_commands_file(client: &mut CDTClient, path: &str, repl_state: ReplState) -> ReplState {
+    let read_result = std::fs::canonicalize(path)
+        .and_then(|canonical_path| Ok((canonical_path, read_commands(path)?)));
+
+    let (canonical_path, commands) = match read_result {
+        Ok(read_result) => read_result,
+        Err(err) => {
+            println!("Error while reading {}: {}", path, err);
+            return repl_state.fail();
+        }
+    };
+
+    if repl_state.sourced_files.contains(&canonical_path) {
+        println!("Error: {} is already being run", path);
+        return repl_state.fail();
+    }
+
+    let mut sourced_files = repl_state.sourced_files.clone();
+    sourced_files.push(canonical_path);
+
+    let repl_state = run_commands(
+        client,
+        path,
+        commands,
+        ReplState {
+            sourced_files,
+            ..repl_state
+        },
+    );
+
+    let mut sourced_files = repl_state.sourced_files.clone();
+    sourced_files.pop();
+
+    ReplState {
+        sourced_files,
+        ..repl_state
+    }
+}
+
+fn run_commands(
+    client: &mut CDTClient,
+    path: &str,
+    commands: Vec<(usize, String)>,
+    repl_state: ReplState,
+) -> ReplState {
+    let mut repl_state = repl_state;
+
+    for (line_number, command) in commands {
+        if matches!(repl_state.debugger_state, DebuggerState::Exited) {
+            println!(
+                "Error: debugger exited before {}:{}: {}",
+                path, line_number, command
+            );
+            return repl_state.fail();
+        }
+
+        println!(">> {}", command.replace('\n', "\n.. "));
+        repl_state = run_command(client, &command, repl_state);
+
+        if repl_state.command_failed {
+            println!("Command failed at {}:{}: {}", path, line_number, command);
+            return repl_state;
+        }
+    }
+
+    repl_state
+}
+
+/// Read commands with their line numbers. Empty lines and lines starting with `#` are
+/// skipped, incomplete JS snippets continue on the following lines.
+fn read_commands(path: &str) -> Result<Vec<(usize, String)>, std::io::Error> {
+    let content = std::fs::read_to_string(path)?;
+    let mut lines = content.lines().enumerate();
+    let mut commands = Vec::new();
+
+    while let Some((i, line)) = lines.next() {
+        let trimmed_line = line.trim();
+
+        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+            continue;
+        }
+
+        let mut command = trimmed_line.to_owned();
+
+        while is_incomplete_input(&command) {
+            match lines.next() {
+                Some((_, line)) => {
+                    command.push('\n');
+                    command.push_str(line);
+                }
+                None => break,
+            }
+        }
+
+        commands.push((i + 1, command));
+    }
+
+    Ok(commands)
+}
@@ -0,0 +1,32 @@
+use crate::cdt::client::CDTClient;
+use crate::cdt::models::DebuggerLocation;
+
+/// Format a location as `<file>:<line>` (1-based), mapped to the original source file when
+/// the script has an inline source map.
+pub fn format_location(client: &mut CDTClient, location: &DebuggerLocation) -> String {
+    let url = client
+        .scripts()
+        .get(&location.script_id)
+        .map(|script| script.url.to_owned())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| location.script_id.to_owned());
+
+    let source_code = match client.script_source_code(location.script_id.to_owned()) {
+        Ok(source_code) => source_code,
+        Err(_) => return format!("{}:{}", url, location.line_number + 1),
+    };
+
+    let original_location = source_code
+        .source_mapping
+        .as_ref()
+        .and_then(|source_mapping| {
+            let token =
+                source_mapping.lookup_token(location.line_number, location.column_number)?;
+            Some((token.get_source()?.to_owned(), token.get_src_line()))
+        });
+
+    match original_location {
+        Some((source, line_number)) => format!("{}:{}", source, line_number + 1),
+        None => format!("{}:{}", url, location.line_number + 1),
+    }
+}
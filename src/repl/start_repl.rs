@@ -1,47 +1,112 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use regex::Regex;
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
 
 use crate::cdt::client::CDTClient;
+use crate::config::Config;
 
+use super::alloc_command::alloc_command;
+use super::apply_command::apply_command;
+use super::async_next_command::async_next_command;
+use super::backtrace_command::backtrace_command;
+use super::blackbox_command::{
+    blackbox_command, compile_blackbox_patterns, set_blackbox_patterns, unblackbox_command,
+};
+use super::breakpoint_command::{
+    breakpoint_command, function_breakpoint_command, list_breakpoints_command,
+};
 use super::continue_to_command::continue_to_command;
+use super::coverage_command::coverage_command;
+use super::display_command::{display_command, undisplay_command};
 use super::evaluate_command::{
     evaluate_expression, evaluate_expression_from_command, evalulate_and_stringify_command,
 };
+use super::execution_context_command::{context_command, contexts_command};
 use super::handle_pause_of_destry_message::handle_pause_or_destroy_message;
-use super::repl_state::{DebuggerState, ReplState, ReplStateCallFrame};
-use super::show_source_code_command::{show_source_code_command, show_minified_source_code_command};
+use super::heap_command::heap_command;
+use super::leak_command::leak_command;
+use super::locals_command::locals_command;
+use super::multiline_input::is_incomplete_input;
+use super::object_handles::release_object_handles;
+use super::profile_command::profile_command;
+use super::repl_helper::ReplHelper;
+use super::repl_state::{DebuggerState, ReplState};
+use super::restart_frame_command::restart_frame_command;
+use super::return_value_command::return_command;
+use super::set_variable_command::set_variable_command;
+use super::show_source_code_command::{
+    show_minified_source_code_command, show_source_code_command,
+};
+use super::source_command::{run_commands_file, source_command};
+use super::step_command::{
+    finish_command, next_command, step_into_command, step_out_command, until_command,
+};
+use super::watch_command::{list_watches_command, unwatch_command, watch_command};
 
-pub fn start_repl(host: &str, port: &str, id: &str) {
-    let mut client = CDTClient::new(host, port, id);
+static ASYNC_CALL_STACK_DEPTH: u32 = 32;
+
+pub fn start_repl(host: &str, port: &str, id: &str, config: &Config, commands: Option<&str>) {
+    let client = Rc::new(RefCell::new(CDTClient::new(host, port, id)));
     let history_file_path = ".node-debug.history";
 
-    let mut rl = Editor::<()>::new();
+    let blackbox_patterns = compile_blackbox_patterns(&config.blackbox).unwrap_or_else(|err| {
+        println!("Error while reading blackbox patterns: {}", err);
+        std::process::exit(1);
+    });
 
-    if rl.load_history(history_file_path).is_err() {
-        println!("No previous history.");
-    }
+    let mut repl_state = {
+        let mut client = client.borrow_mut();
+
+        client.runtime_enable().unwrap();
+        client.debugger_enable().unwrap();
+        client.debugger_set_pause_on_exception().unwrap();
+        client
+            .debugger_set_async_call_stack_depth(ASYNC_CALL_STACK_DEPTH)
+            .unwrap();
+        client.profiler_enable().unwrap();
+        client.heap_profiler_enable().unwrap();
 
-    client.runtime_enable().unwrap();
-    client.debugger_enable().unwrap();
-    client.debugger_set_pause_on_exception().unwrap();
-    client.profiler_enable().unwrap();
-    client.debugger_pause().unwrap();
+        if let Err(err) = set_blackbox_patterns(&mut client, &blackbox_patterns) {
+            println!("Error while setting blackbox patterns: {}", err);
+        }
 
-    println!("Waiting for the debugger...");
-    let mut repl_state = initialize(&mut client);
+        println!("Waiting for the debugger...");
+        initialize(&mut client, config, blackbox_patterns)
+    };
 
     if matches!(repl_state.debugger_state, DebuggerState::Exited) {
         println!("Debugger context destroyed...");
         std::process::exit(1);
     }
 
+    if let Some(path) = commands {
+        let repl_state = run_commands_file(&mut client.borrow_mut(), path, repl_state);
+        std::process::exit(if repl_state.command_failed { 1 } else { 0 });
+    }
+
+    let mut rl = Editor::<ReplHelper>::new();
+
+    if rl.load_history(history_file_path).is_err() {
+        println!("No previous history.");
+    }
+
+    rl.set_helper(Some(ReplHelper::new(client.clone(), repl_state.clone())));
+
     loop {
-        let readline = rl.readline(">> ");
+        let readline = read_input(&mut rl);
 
         match readline {
+            Ok(line) if line.is_empty() => continue,
             Ok(line) => {
                 rl.add_history_entry(line.as_str());
-                repl_state = run_command(&mut client, &line, repl_state);
+                repl_state = run_command(&mut client.borrow_mut(), &line, repl_state);
+
+                if let Some(helper) = rl.helper_mut() {
+                    helper.set_repl_state(repl_state.clone());
+                }
 
                 if matches!(repl_state.debugger_state, DebuggerState::Exited) {
                     break;
@@ -65,94 +130,258 @@ pub fn start_repl(host: &str, port: &str, id: &str) {
     rl.save_history(history_file_path).unwrap();
 }
 
-fn initialize(client: &mut CDTClient) -> ReplState {
-    let message = client.runtime_run_if_waiting_for_debugger().unwrap();
+/// Read a line and keep reading continuation lines with a `..` prompt while the input
+/// is an incomplete JS snippet. CTRL-C on a continuation line discards the snippet.
+fn read_input(rl: &mut Editor<ReplHelper>) -> Result<String, ReadlineError> {
+    let mut input = rl.readline(">> ")?;
 
-    match message {
-        Some(message) => ReplState {
-            call_frames: Some(ReplStateCallFrame {
-                call_frames: message.params.call_frames.clone(),
-                active_id: 0,
-            }),
-            debugger_state: DebuggerState::Paused,
-        },
-        None => ReplState {
-            call_frames: None,
-            debugger_state: DebuggerState::Exited,
-        },
+    while is_incomplete_input(&input) {
+        match rl.readline(".. ") {
+            Ok(line) => {
+                input.push('\n');
+                input.push_str(&line);
+            }
+            Err(ReadlineError::Interrupted) => return Ok(String::new()),
+            Err(err) => return Err(err),
+        }
     }
+
+    Ok(input)
 }
 
-fn run_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
-    match line {
+fn initialize(client: &mut CDTClient, config: &Config, blackbox_patterns: Vec<Regex>) -> ReplState {
+    let repl_state = ReplState {
+        display: config.display.clone(),
+        blackbox_patterns,
+        ..ReplState::new()
+    };
+
+    let message = client.runtime_run_if_waiting_for_debugger().unwrap();
+    handle_pause_or_destroy_message(client, message, repl_state)
+}
+
+pub fn run_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let repl_state = ReplState {
+        command_failed: false,
+        ..repl_state
+    };
+
+    let repl_state = match line {
         "s" | "show" => show_source_code_command(client, repl_state),
         "sm" | "show-minified" => show_minified_source_code_command(client, repl_state),
         "c" | "continue" => continue_command(client, repl_state),
-        "i" | "step-into" => step_into_command(client, repl_state),
-        "o" | "step-out" => step_out_command(client, repl_state),
+        "resume" => resume_command(client, repl_state),
+        "pause" => pause_command(client, repl_state),
+        "contexts" => contexts_command(client, repl_state),
+        cmd if cmd.starts_with("context ") => context_command(client, cmd, repl_state),
+        cmd if is_command_with_count(cmd, &["i", "step-into"]) => {
+            step_into_command(client, cmd, repl_state)
+        }
+        cmd if is_command_with_count(cmd, &["o", "step-out"]) => {
+            step_out_command(client, cmd, repl_state)
+        }
+        "u" | "until" => until_command(client, repl_state),
+        "finish" => finish_command(client, repl_state),
+        cmd if cmd.starts_with("return ") => return_command(client, cmd, repl_state),
+        "bl" | "breakpoints" => list_breakpoints_command(repl_state),
+        cmd if cmd.starts_with("b ") || cmd.starts_with("break ") => {
+            breakpoint_command(client, cmd, repl_state)
+        }
+        cmd if cmd.starts_with("break-fn ") => function_breakpoint_command(client, cmd, repl_state),
+        cmd if is_command_with_count(cmd, &["ns"]) => {
+            next_and_show_command(client, cmd, repl_state)
+        }
+        "an" | "async-next" => async_next_command(client, repl_state),
+        "l" | "locals" => locals_command(client, repl_state),
+        "bt" | "backtrace" => backtrace_command(client, repl_state),
+        "display" => display_command(line, repl_state),
+        cmd if cmd.starts_with("display ") => display_command(cmd, repl_state),
+        cmd if cmd.starts_with("undisplay ") => undisplay_command(cmd, repl_state),
+        "watches" => list_watches_command(client, repl_state),
+        cmd if cmd == "heap" || cmd.starts_with("heap ") => heap_command(client, cmd, repl_state),
+        cmd if cmd == "leak" || cmd.starts_with("leak ") => leak_command(client, cmd, repl_state),
+        cmd if cmd == "profile" || cmd.starts_with("profile ") => {
+            profile_command(client, cmd, repl_state)
+        }
+        cmd if cmd == "alloc" || cmd.starts_with("alloc ") => {
+            alloc_command(client, cmd, repl_state)
+        }
+        cmd if cmd == "coverage" || cmd.starts_with("coverage ") => {
+            coverage_command(client, cmd, repl_state)
+        }
+        cmd if cmd == "blackbox" || cmd.starts_with("blackbox ") => {
+            blackbox_command(client, cmd, repl_state)
+        }
+        cmd if cmd == "unblackbox" || cmd.starts_with("unblackbox ") => {
+            unblackbox_command(client, cmd, repl_state)
+        }
+        cmd if cmd.starts_with("source ") => source_command(client, cmd, repl_state),
+        cmd if cmd.starts_with("set ") => set_variable_command(client, cmd, repl_state),
+        cmd if cmd.starts_with("apply ") || cmd.starts_with("hotfix ") => {
+            apply_command(client, cmd, repl_state)
+        }
+        cmd if cmd == "restart-frame" || cmd.starts_with("restart-frame ") => {
+            restart_frame_command(client, cmd, repl_state)
+        }
+        cmd if cmd.starts_with("watch ") => watch_command(cmd, repl_state),
+        cmd if cmd.starts_with("unwatch ") => unwatch_command(cmd, repl_state),
         cmd if cmd.starts_with("ct ") => continue_to_command(client, line, repl_state),
         cmd if cmd.starts_with("e ") => evaluate_expression_from_command(client, cmd, repl_state),
         cmd if cmd.starts_with("es ") => evalulate_and_stringify_command(client, cmd, repl_state),
-        "n" | "next" => next_command(client, repl_state),
-        "q" | "quit" => quit_command(),
+        cmd if is_command_with_count(cmd, &["n", "next"]) => next_command(client, cmd, repl_state),
+        "q" | "quit" => quit_command(repl_state),
         "h" | "help" => help_command(client, repl_state),
         _ => evaluate_expression(client, line, repl_state),
-    }
-}
+    };
 
-fn step_into_command(client: &mut CDTClient, _: ReplState) -> ReplState {
-    let message = client.debugger_step_into().unwrap();
-    handle_pause_or_destroy_message(message)
-}
+    // The program resumed with `resume` may have hit a breakpoint while the command ran.
+    if matches!(repl_state.debugger_state, DebuggerState::Running) {
+        if let Some(message) = client.take_pending_pause() {
+            return handle_pause_or_destroy_message(client, Some(message), repl_state);
+        }
+    }
 
-fn step_out_command(client: &mut CDTClient, _: ReplState) -> ReplState {
-    let message = client.debugger_step_out().unwrap();
-    handle_pause_or_destroy_message(message)
+    repl_state
 }
 
-fn quit_command() -> ReplState {
+fn quit_command(repl_state: ReplState) -> ReplState {
     println!("Exiting, see ya!");
     ReplState {
         debugger_state: DebuggerState::Exited,
         call_frames: None,
+        ..repl_state
     }
 }
 
 fn continue_command(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
     if !matches!(repl_state.debugger_state, DebuggerState::Paused) {
         println!("Error: debugger is not paused");
-        return repl_state;
+        return repl_state.fail();
     }
 
     client.debugger_resume().unwrap();
     let message = client.runtime_run_if_waiting_for_debugger().unwrap();
-    handle_pause_or_destroy_message(message)
+    let repl_state = ReplState {
+        continued: true,
+        ..repl_state
+    };
+    handle_pause_or_destroy_message(client, message, repl_state)
 }
 
-fn next_command(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+/// Resume the execution and return to the prompt right away, expressions are evaluated in
+/// the selected execution context until the program pauses again.
+fn resume_command(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
     if !matches!(repl_state.debugger_state, DebuggerState::Paused) {
         println!("Error: debugger is not paused");
-        return repl_state;
+        return repl_state.fail();
     }
 
-    client.debugger_step_over().unwrap();
-    let message = client.runtime_run_if_waiting_for_debugger().unwrap();
-    handle_pause_or_destroy_message(message)
+    let repl_state = release_object_handles(client, repl_state);
+
+    if let Err(err) = client.debugger_run() {
+        println!("Error while resuming: {}", err);
+        return repl_state.fail();
+    }
+
+    println!("Running, use pause to stop the program");
+    ReplState {
+        debugger_state: DebuggerState::Running,
+        call_frames: None,
+        continued: true,
+        ..repl_state
+    }
+}
+
+fn pause_command(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    if !matches!(repl_state.debugger_state, DebuggerState::Running) {
+        println!("Error: program is not running");
+        return repl_state.fail();
+    }
+
+    let repl_state = ReplState {
+        continued: false,
+        ..repl_state
+    };
+
+    match client.debugger_interrupt() {
+        Ok(message) => handle_pause_or_destroy_message(client, message, repl_state),
+        Err(err) => {
+            println!("Error while pausing: {}", err);
+            repl_state.fail()
+        }
+    }
+}
+
+fn next_and_show_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let repl_state = next_command(client, line, repl_state);
+
+    if matches!(repl_state.debugger_state, DebuggerState::Paused) {
+        show_source_code_command(client, repl_state)
+    } else {
+        repl_state
+    }
+}
+
+/// Check whether the line is one of the commands, optionally followed by a count. Anything
+/// else after the command is an expression like `i + 1` or `n * 2` to evaluate.
+fn is_command_with_count(line: &str, commands: &[&str]) -> bool {
+    match line.split_once(' ') {
+        Some((command, count)) => {
+            commands.contains(&command)
+                && matches!(count.trim().parse::<usize>(), Ok(count) if count > 0)
+        }
+        None => commands.contains(&line),
+    }
 }
 
 fn help_command(_: &mut CDTClient, repl_state: ReplState) -> ReplState {
     let help = "s / show                 show the original source code of the current call frame\n\
                 sm / show-minified       show minified source code of the current call frame\n\
                 c / continue             resume the execution\n\
-                i / step-into            step out of the function\n\
-                o / step-out             step into the function\n\
-                n / next                 step over in the execution\n\
+                resume                   resume the execution and keep the prompt, expressions run in the selected context\n\
+                pause                    pause the program resumed with resume\n\
+                contexts                 list execution contexts\n\
+                context <id>             select the context expressions run in while the program is running\n\
+                i / step-into [<count>]  step into the function or the scheduled async call\n\
+                o / step-out [<count>]   step out of the function\n\
+                n / next [<count>]       step over in the execution\n\
+                ns [<count>]             step over and show the source code\n\
+                u / until                step over until a later line of the current function\n\
+                finish                   run until the current function returns and show the return value\n\
+                return <expr>            change the value returned by the function paused at its return\n\
+                an / async-next          step over until the same (async) function is resumed\n\
+                l / locals               show variables of the current call frame\n\
+                set <name> = <expr>      set a variable in the scope of the current call frame declaring it\n\
+                bt / backtrace           show the call frames\n\
+                restart-frame [<n>]      restart the n-th call frame (the active one by default)\n\
+                apply / hotfix <file>    replace the loaded script with the edited local file\n\
                 ct                       continue to a line (in the minified file)\n\
+                b / break <url>:<line>   set a breakpoint, <url> can be a suffix of the script URL\n\
+                break-fn <expr>          set a breakpoint on entry of the function the expression evaluates to\n\
+                bl / breakpoints         list breakpoints\n\
+                watch <expression>       evaluate the expression whenever the debugger stops\n\
+                unwatch <n>              remove the n-th watch expression\n\
+                watches                  show values of the watch expressions\n\
+                display [<command>]      list or add commands run whenever the debugger stops\n\
+                undisplay <n>            remove the n-th display command\n\
+                blackbox [<regex>]       list or add patterns of script URLs to skip when stepping\n\
+                unblackbox [<n>]         remove the n-th blackbox pattern (all by default)\n\
+                profile start [--interval <us>]  start CPU profiling\n\
+                profile stop [<file>]    save the CPU profile and show the functions with the most self time\n\
+                coverage start           start collecting precise coverage with call counts\n\
+                coverage stop <file>     save an LCOV report and show the line coverage of each file\n\
+                heap snapshot [<file>]   save a heap snapshot loadable in Chrome DevTools\n\
+                alloc start              start sampling allocations\n\
+                alloc stop [<file>]      save the allocation profile and show the stacks retaining the most memory\n\
+                leak start               force GC and take a baseline heap snapshot\n\
+                leak check               force GC, take a snapshot and show what grew since leak start\n\
+                source <file>            run commands from a file, stop on the first failure\n\
                 q / quit                 quit the debugger\n\
                 h / help                 show this help\n\
                 es <expresssion>         evalute JS expression and stringify it in the current call frame\n\
                 e <expresssion>          evalute JS expression in the current call frame\n\
-                <expression>             evalute JS expression in the current call frame";
+                <expression>             evalute JS expression in the current call frame (or the selected context when running), awaiting promises\n\
+                $<n>                     printed object, usable in expressions until the program resumes";
 
     println!("{}", help);
 
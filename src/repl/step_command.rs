@@ -0,0 +1,381 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use crate::cdt::client::CDTClient;
+use crate::cdt::models::{
+    DebuggerPausedCallFrame, DebuggerPausedResponse, DebuggerScriptParsedResponseParams,
+    RuntimeRemoteObjectResult, RuntimeScriptId,
+};
+
+use super::blackbox_command::is_blackboxed;
+use super::handle_pause_of_destry_message::handle_pause_or_destroy_message;
+use super::render_remote_object::runtime_remote_object_to_string;
+use super::repl_state::{DebuggerState, ReplState};
+
+static MAX_UNTIL_STEPS: usize = 1000;
+static MAX_BLACKBOXED_STEPS: usize = 5000;
+
+pub fn next_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let blackbox_patterns = repl_state.blackbox_patterns.clone();
+
+    step_command(client, line, repl_state, |client| {
+        client.debugger_step_over().unwrap();
+        let message = client.runtime_run_if_waiting_for_debugger().unwrap();
+        step_past_blackboxed_frames(client, message, &blackbox_patterns)
+    })
+}
+
+pub fn step_into_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    let blackbox_patterns = repl_state.blackbox_patterns.clone();
+
+    step_command(client, line, repl_state, |client| {
+        let message = client.debugger_step_into().unwrap();
+        step_past_blackboxed_frames(client, message, &blackbox_patterns)
+    })
+}
+
+pub fn step_out_command(client: &mut CDTClient, line: &str, repl_state: ReplState) -> ReplState {
+    // Stepping out from the return position drops the return value, show it before it's gone.
+    let top_call_frame = repl_state
+        .call_frames
+        .as_ref()
+        .and_then(|call_frames| call_frames.call_frames.first());
+
+    if let Some(call_frame) = top_call_frame {
+        if let Some(return_value) = &call_frame.return_value {
+            print_returned(&call_frame.function_name, return_value.clone());
+        }
+    }
+
+    let blackbox_patterns = repl_state.blackbox_patterns.clone();
+
+    step_command(client, line, repl_state, |client| {
+        let message = client.debugger_step_out().unwrap();
+        step_past_blackboxed_frames(client, message, &blackbox_patterns)
+    })
+}
+
+/// Step over until the debugger stops on a line after the current one in the same frame,
+/// or the frame returns. Useful to leave a loop without setting a breakpoint.
+pub fn until_command(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    let frame = match current_frame(&repl_state) {
+        Some(frame) => frame,
+        None => return repl_state.fail(),
+    };
+
+    let line_number = frame.call_frame.location.line_number;
+    let mut message = None;
+
+    for _ in 0..MAX_UNTIL_STEPS {
+        client.debugger_step_over().unwrap();
+        message = client.runtime_run_if_waiting_for_debugger().unwrap();
+
+        let is_done = match message.as_ref() {
+            None => true,
+            Some(message) => match frame.compare(message) {
+                FramePosition::Same(call_frame) => call_frame.location.line_number > line_number,
+                FramePosition::Outer => true,
+                FramePosition::Inner => false,
+            },
+        };
+
+        if is_done {
+            return handle_pause_or_destroy_message(client, message, repl_state);
+        }
+    }
+
+    println!(
+        "Error: no later line was reached within {} steps",
+        MAX_UNTIL_STEPS
+    );
+    handle_pause_or_destroy_message(client, message, repl_state).fail()
+}
+
+/// Run until the current function returns, print its return value and stop in the caller.
+pub fn finish_command(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    let frame = match current_frame(&repl_state) {
+        Some(frame) => frame,
+        None => return repl_state.fail(),
+    };
+
+    let function_name = display_function_name(&frame.call_frame.function_name);
+    let mut return_value = frame.call_frame.return_value.clone();
+
+    // V8 reports the return value only when paused at the return position of the frame,
+    // so step over within the frame until it gets there.
+    for _ in 0..MAX_UNTIL_STEPS {
+        if return_value.is_some() {
+            break;
+        }
+
+        client.debugger_step_over().unwrap();
+        let message = client.runtime_run_if_waiting_for_debugger().unwrap();
+
+        match message.as_ref().map(|message| frame.compare(message)) {
+            Some(FramePosition::Same(call_frame)) => {
+                return_value = call_frame.return_value.clone();
+            }
+            Some(FramePosition::Inner) => {}
+            Some(FramePosition::Outer) | None => {
+                println!("{} did not return", function_name);
+                return handle_pause_or_destroy_message(client, message, repl_state);
+            }
+        }
+    }
+
+    let return_value = match return_value {
+        Some(return_value) => return_value,
+        None => {
+            println!(
+                "Error: {} did not return within {} steps",
+                function_name, MAX_UNTIL_STEPS
+            );
+            return repl_state.fail();
+        }
+    };
+
+    print_returned(function_name, return_value);
+    let message = client.debugger_step_out().unwrap();
+    handle_pause_or_destroy_message(client, message, repl_state)
+}
+
+/// Repeat the step as many times as the optional count after the command says. Display
+/// commands run only once the last step stops.
+fn step_command<F>(client: &mut CDTClient, line: &str, repl_state: ReplState, step: F) -> ReplState
+where
+    F: Fn(&mut CDTClient) -> Option<DebuggerPausedResponse>,
+{
+    if !matches!(repl_state.debugger_state, DebuggerState::Paused) {
+        println!("Error: debugger is not paused");
+        return repl_state.fail();
+    }
+
+    let count_string = line.split_once(' ').map(|(_, n)| n.trim()).unwrap_or("1");
+    let count = match count_string.parse::<usize>() {
+        Ok(count) if count > 0 => count,
+        _ => {
+            println!("Expected number of steps, got {}", count_string);
+            return repl_state.fail();
+        }
+    };
+
+    let mut message = None;
+
+    for _ in 0..count {
+        message = step(client);
+
+        if message.is_none() {
+            break;
+        }
+    }
+
+    handle_pause_or_destroy_message(client, message, repl_state)
+}
+
+enum FramePosition<'a> {
+    /// Paused in the original frame.
+    Same(&'a DebuggerPausedCallFrame),
+    /// Paused in a function called from the original frame.
+    Inner,
+    /// The original frame has returned or thrown.
+    Outer,
+}
+
+struct CurrentFrame {
+    call_frame: DebuggerPausedCallFrame,
+    depth: usize,
+}
+
+impl CurrentFrame {
+    /// Find where the pause is relative to this frame by comparing the stack depth, frames
+    /// at the same depth are the same if they run the same function.
+    fn compare<'a>(&self, message: &'a DebuggerPausedResponse) -> FramePosition<'a> {
+        let call_frames = &message.params.call_frames;
+
+        match call_frames.len() {
+            depth if depth > self.depth => FramePosition::Inner,
+            depth if depth < self.depth => FramePosition::Outer,
+            _ => match call_frames.first() {
+                Some(call_frame)
+                    if call_frame.function_location == self.call_frame.function_location =>
+                {
+                    FramePosition::Same(call_frame)
+                }
+                _ => FramePosition::Outer,
+            },
+        }
+    }
+}
+
+fn current_frame(repl_state: &ReplState) -> Option<CurrentFrame> {
+    if !matches!(repl_state.debugger_state, DebuggerState::Paused) {
+        println!("Error: debugger is not paused");
+        return None;
+    }
+
+    let call_frames = repl_state
+        .call_frames
+        .as_ref()
+        .map(|call_frames| &call_frames.call_frames)
+        .filter(|call_frames| !call_frames.is_empty());
+
+    match call_frames {
+        Some(call_frames) => Some(CurrentFrame {
+            call_frame: call_frames[0].clone(),
+            depth: call_frames.len(),
+        }),
+        None => {
+            println!("Error: no active call frame");
+            None
+        }
+    }
+}
+
+/// Keep stepping into while the pause is in a blackboxed script, until it gets to user code.
+/// V8 stops in node internals even when they match the blackbox patterns, when stepping into
+/// them or returning to them, and async calls like `setTimeout` are only caught once the
+/// internals schedule the task.
+fn step_past_blackboxed_frames(
+    client: &mut CDTClient,
+    message: Option<DebuggerPausedResponse>,
+    blackbox_patterns: &[Regex],
+) -> Option<DebuggerPausedResponse> {
+    let mut message = message;
+
+    for _ in 0..MAX_BLACKBOXED_STEPS {
+        match &message {
+            Some(paused)
+                if is_paused_in_blackboxed_frame(client.scripts(), paused, blackbox_patterns) =>
+            {
+                message = client.debugger_step_into().unwrap();
+            }
+            _ => return message,
+        }
+    }
+
+    println!(
+        "Still in blackboxed code after {} steps",
+        MAX_BLACKBOXED_STEPS
+    );
+    message
+}
+
+/// Whether the top frame of the pause runs a script matching the blackbox patterns.
+fn is_paused_in_blackboxed_frame(
+    scripts: &HashMap<RuntimeScriptId, DebuggerScriptParsedResponseParams>,
+    message: &DebuggerPausedResponse,
+    blackbox_patterns: &[Regex],
+) -> bool {
+    message
+        .params
+        .call_frames
+        .first()
+        .and_then(|call_frame| scripts.get(&call_frame.location.script_id))
+        .is_some_and(|script| is_blackboxed(&script.url, blackbox_patterns))
+}
+
+fn print_returned(function_name: &str, return_value: RuntimeRemoteObjectResult) {
+    println!(
+        "{} returned {}",
+        display_function_name(function_name),
+        runtime_remote_object_to_string(return_value)
+    );
+}
+
+fn display_function_name(function_name: &str) -> &str {
+    if function_name.is_empty() {
+        "(anonymous)"
+    } else {
+        function_name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use super::is_paused_in_blackboxed_frame;
+    use crate::cdt::models::{DebuggerPausedResponse, DebuggerScriptParsedResponseParams};
+    use crate::config::Config;
+    use crate::repl::blackbox_command::compile_blackbox_patterns;
+
+    fn script(script_id: &str, url: &str) -> (String, DebuggerScriptParsedResponseParams) {
+        let script = serde_json::from_value(json!({
+            "scriptId": script_id,
+            "url": url,
+            "startLine": 0,
+            "startColumn": 0,
+            "endLine": 0,
+            "endColumn": 0,
+            "executionContextId": 1,
+            "hash": ""
+        }))
+        .unwrap();
+
+        (script_id.to_owned(), script)
+    }
+
+    /// A pause with the frames given as (function name, script id), the top frame first.
+    fn paused(frames: &[(&str, &str)]) -> DebuggerPausedResponse {
+        let call_frames = frames
+            .iter()
+            .enumerate()
+            .map(|(i, (function_name, script_id))| {
+                let location = json!({ "scriptId": script_id, "lineNumber": 0 });
+                json!({
+                    "callFrameId": i.to_string(),
+                    "functionName": function_name,
+                    "functionLocation": location,
+                    "location": location
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::from_value(json!({ "params": { "callFrames": call_frames } })).unwrap()
+    }
+
+    #[test]
+    fn timer_callbacks() {
+        let scripts = HashMap::from([
+            script("1", "file:///app/main.js"),
+            script("2", "node:timers"),
+            script("3", "node:internal/timers"),
+        ]);
+        let patterns = compile_blackbox_patterns(&["^node:".to_owned()]).unwrap();
+
+        // `i` on `setTimeout(later, 10)` first stops inside `setTimeout`.
+        let in_set_timeout = paused(&[("setTimeout", "2"), ("main", "1")]);
+        assert!(is_paused_in_blackboxed_frame(&scripts, &in_set_timeout, &patterns));
+
+        // The step is done once the timer calls back into user code.
+        let in_callback = paused(&[
+            ("later", "1"),
+            ("listOnTimeout", "3"),
+            ("processTimers", "3"),
+        ]);
+        assert!(!is_paused_in_blackboxed_frame(&scripts, &in_callback, &patterns));
+    }
+
+    #[test]
+    fn node_internal_callees() {
+        let scripts = HashMap::from([
+            script("1", "file:///app/main.js"),
+            script("2", "node:path"),
+            script("3", "file:///app/node_modules/lib/index.js"),
+        ]);
+        let patterns = compile_blackbox_patterns(&Config::default().blackbox).unwrap();
+
+        // `i` on `path.join(a, b)` stops in the node internal first.
+        let in_join = paused(&[("join", "2"), ("main", "1")]);
+        assert!(is_paused_in_blackboxed_frame(&scripts, &in_join, &patterns));
+
+        let in_dependency = paused(&[("lib", "3"), ("main", "1")]);
+        assert!(is_paused_in_blackboxed_frame(&scripts, &in_dependency, &patterns));
+
+        let after_join = paused(&[("main", "1")]);
+        assert!(!is_paused_in_blackboxed_frame(&scripts, &after_join, &patterns));
+    }
+}
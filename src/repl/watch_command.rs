@@ -0,0 +1,101 @@
+use crate::cdt::client::CDTClient;
+use crate::color::{colored, GRAY, LIGHT_RED};
+
+use super::render_remote_object::runtime_remote_object_to_string;
+use super::repl_state::ReplState;
+
+pub fn watch_command(line: &str, repl_state: ReplState) -> ReplState {
+    let expression = line
+        .split_once(' ')
+        .map(|(_, expression)| expression.trim());
+
+    let expression = match expression {
+        Some(expression) if !expression.is_empty() => expression,
+        _ => {
+            println!("Expected expression to watch");
+            return repl_state.fail();
+        }
+    };
+
+    let mut watches = repl_state.watches.clone();
+    watches.push(expression.to_owned());
+    println!("Watch #{} added: {}", watches.len(), expression);
+
+    ReplState {
+        watches,
+        ..repl_state
+    }
+}
+
+pub fn unwatch_command(line: &str, repl_state: ReplState) -> ReplState {
+    let number_string = line.split_once(' ').map(|(_, n)| n.trim()).unwrap_or("");
+    let number: Option<usize> = number_string.parse().ok();
+
+    let index = match number {
+        Some(number) if number > 0 && number <= repl_state.watches.len() => number - 1,
+        _ => {
+            println!("Expected watch number, got {}", number_string);
+            return repl_state.fail();
+        }
+    };
+
+    let mut watches = repl_state.watches.clone();
+    let expression = watches.remove(index);
+    println!("Watch #{} removed: {}", index + 1, expression);
+
+    ReplState {
+        watches,
+        ..repl_state
+    }
+}
+
+pub fn list_watches_command(client: &mut CDTClient, repl_state: ReplState) -> ReplState {
+    if repl_state.watches.is_empty() {
+        println!("No watch expressions.");
+    } else {
+        print_watches(client, &repl_state);
+    }
+
+    repl_state
+}
+
+/// Evaluate all watch expressions in the active call frame and print them as a block.
+/// Evaluation errors are printed next to the expression instead of aborting the block.
+pub fn print_watches(client: &mut CDTClient, repl_state: &ReplState) {
+    if repl_state.watches.is_empty() {
+        return;
+    }
+
+    let call_frame_id = repl_state
+        .get_active_call_frame()
+        .map(|call_frame| call_frame.call_frame_id.to_owned());
+
+    println!("{}", colored(GRAY, "watches"));
+
+    for (i, expression) in repl_state.watches.iter().enumerate() {
+        let value = match &call_frame_id {
+            Some(call_frame_id) => evaluate_watch(client, call_frame_id, expression),
+            None => colored(LIGHT_RED, "<no active frame>"),
+        };
+
+        println!(" {}: {} = {}", i + 1, expression, value);
+    }
+}
+
+fn evaluate_watch(client: &mut CDTClient, call_frame_id: &str, expression: &str) -> String {
+    match client.debugger_evaluate_on_call_frame(call_frame_id.to_owned(), expression) {
+        Ok(remote_object) => match remote_object.exception_details {
+            Some(exception_details) => {
+                let message = exception_details
+                    .exception
+                    .and_then(|exception| exception.description)
+                    .and_then(|description| description.lines().next().map(str::to_owned))
+                    .unwrap_or(exception_details.text);
+
+                colored(LIGHT_RED, &format!("<{}>", message))
+            }
+            None => runtime_remote_object_to_string(remote_object.result),
+        },
+        Err(err) => colored(LIGHT_RED, &format!("<{}>", err)),
+    }
+}